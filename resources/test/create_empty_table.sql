@@ -18,5 +18,7 @@ CREATE TABLE IF NOT EXISTS textual_objects
     json           JSONB DEFAULT '{}' NOT NULL,
 
     card           JSONB DEFAULT NULL,
-    card_map       TEXT  DEFAULT ''   NOT NULL
+    card_map       TEXT  DEFAULT ''   NOT NULL,
+
+    version        INTEGER DEFAULT 1  NOT NULL
 )
@@ -43,6 +43,8 @@ pub(crate) async fn initialize_database(db_root_path: &str, db_file_name: &str)
     let if_tables_exist = check_if_tables_exist(&pool).await.unwrap();
     if !if_tables_exist {
         create_initial_table(&pool).await;
+    } else {
+        add_missing_columns(&pool).await;
     }
     pool.close().await;
     Ok(db_path)
@@ -61,6 +63,21 @@ async fn check_if_tables_exist(p0: &Pool<Sqlite>) -> Result<bool, sqlx::Error> {
 }
 
 
+// bring tables created by earlier versions up to date
+async fn add_missing_columns(pool: &Pool<Sqlite>) {
+    let columns = sqlx::query("SELECT name FROM pragma_table_info('textual_objects')")
+        .fetch_all(pool)
+        .await
+        .unwrap();
+    let has_version = columns.iter().any(|row| row.get::<String, _>("name") == "version");
+    if !has_version {
+        sqlx::query("ALTER TABLE textual_objects ADD COLUMN version INTEGER DEFAULT 1 NOT NULL")
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
 // create empty database
 pub(crate) async fn create_empty_database(db_path: &str) {
     Sqlite::create_database(db_path).await.unwrap();
@@ -96,7 +113,9 @@ async fn create_initial_table(pool: &Pool<Sqlite>) {
     json           JSONB DEFAULT '{}' NOT NULL,
 
     card           JSONB DEFAULT NULL,
-    card_map       TEXT  DEFAULT ''   NOT NULL
+    card_map       TEXT  DEFAULT ''   NOT NULL,
+
+    version        INTEGER DEFAULT 1  NOT NULL
 )"
 
     )
@@ -1,5 +1,6 @@
 use std::borrow::BorrowMut;
 
+use indexmap::IndexMap;
 use sqlx::{Error, Pool, Row, Sqlite};
 use sqlx::pool::PoolConnection;
 use sqlx::sqlite::{SqliteQueryResult, SqliteRow};
@@ -25,9 +26,10 @@ pub(crate) async fn insert_to(pool: &mut PoolConnection<Sqlite>, textual_object:
         json,
         card,
         card_map,
-        ticket_minimal
+        ticket_minimal,
+        version
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
         textual_object.id,
         textual_object.ticket_id,
         textual_object.source_id,
@@ -42,6 +44,7 @@ pub(crate) async fn insert_to(pool: &mut PoolConnection<Sqlite>, textual_object:
         textual_object.card,
         textual_object.card_map,
         textual_object.ticket_minimal,
+        textual_object.version,
     );
     insert_query.execute(pool).await.unwrap();
     textual_object.id
@@ -69,6 +72,19 @@ pub(crate) async fn find_to_by_ticket_id(pool: &mut PoolConnection<Sqlite>, tick
     load_sqlite_row_to_textual_object(textual_object_rows)
 }
 
+// find tos by source name, source id type and/or source id, a None filter matches everything
+pub(crate) async fn find_tos_by_source(pool: &mut PoolConnection<Sqlite>, source_name: Option<&str>, source_id_type: Option<&str>, source_id: Option<&str>) -> Vec<TextualObject> {
+    let textual_object_rows = sqlx::query(
+        "SELECT * FROM textual_objects WHERE ($1 IS NULL OR source_name = $1) AND ($2 IS NULL OR source_id_type = $2) AND ($3 IS NULL OR source_id = $3)",
+    )
+        .bind(source_name)
+        .bind(source_id_type)
+        .bind(source_id)
+        .fetch_all(pool)
+        .await;
+    load_multiple_sqlite_rows_to_textual_objects(textual_object_rows)
+}
+
 // check if there is any row with the given ticket id
 pub(crate) async fn check_if_ticket_id_exists(pool: &mut PoolConnection<Sqlite>, ticket_id: &str) -> bool {
     let textual_object_rows = sqlx::query(
@@ -90,6 +106,53 @@ pub(crate) async fn count_textual_objects(mut pool: PoolConnection<Sqlite>) -> i
     count.get(0)
 }
 
+// count the number of textual objects for each distinct value of a column, e.g. source_name
+pub(crate) async fn count_textual_objects_group_by(pool: &mut PoolConnection<Sqlite>, column: &str) -> IndexMap<String, i64> {
+    let count_query = format!("SELECT {0}, COUNT(*) FROM textual_objects GROUP BY {0} ORDER BY {0}", column);
+    let rows = sqlx::query(&count_query).fetch_all(pool).await.unwrap();
+    let mut counts = IndexMap::new();
+    for row in rows {
+        let value: Option<String> = row.get(0);
+        counts.insert(value.unwrap_or_default(), row.get(1));
+    }
+    counts
+}
+
+// count the number of textual objects per day of a timestamp column, e.g. created
+pub(crate) async fn count_textual_objects_by_day(pool: &mut PoolConnection<Sqlite>, column: &str) -> IndexMap<String, i64> {
+    // timestamps are stored as text starting with YYYY-MM-DD
+    let count_query = format!("SELECT substr({0}, 1, 10) AS day, COUNT(*) FROM textual_objects GROUP BY day ORDER BY day", column);
+    let rows = sqlx::query(&count_query).fetch_all(pool).await.unwrap();
+    let mut counts = IndexMap::new();
+    for row in rows {
+        let day: Option<String> = row.get(0);
+        counts.insert(day.unwrap_or_default(), row.get(1));
+    }
+    counts
+}
+
+// average size in bytes of the json field, 0 when the store is empty
+pub(crate) async fn average_json_size(pool: &mut PoolConnection<Sqlite>) -> f64 {
+    let average_query = sqlx::query("SELECT AVG(LENGTH(CAST(json AS BLOB))) FROM textual_objects");
+    let average = average_query.fetch_one(pool).await.unwrap();
+    let average: Option<f64> = average.get(0);
+    average.unwrap_or(0.0)
+}
+
+// count the number of textual objects whose card has a non-empty title
+pub(crate) async fn count_cards_with_title(pool: &mut PoolConnection<Sqlite>) -> i64 {
+    let count_query = sqlx::query("SELECT COUNT(*) FROM textual_objects WHERE json_extract(card, '$.title') <> ''");
+    let count = count_query.fetch_one(pool).await.unwrap();
+    count.get(0)
+}
+
+// count the number of textual objects whose card has at least one creator
+pub(crate) async fn count_cards_with_creators(pool: &mut PoolConnection<Sqlite>) -> i64 {
+    let count_query = sqlx::query("SELECT COUNT(*) FROM textual_objects WHERE (SELECT COUNT(*) FROM json_each(card, '$.creators')) > 0");
+    let count = count_query.fetch_one(pool).await.unwrap();
+    count.get(0)
+}
+
 // load multiple sqlite rows to textual objecs
 fn load_multiple_sqlite_rows_to_textual_objects(textual_object_rows: Result<Vec<SqliteRow>, Error>) -> Vec<TextualObject> {
     let mut textual_objects = Vec::new();
@@ -135,6 +198,7 @@ fn load_sqlite_row_to_textual_object(textual_object_row: Result<SqliteRow, Error
                 json: textual_object_row.get("json"),
                 card: textual_object_row.get("card"),
                 card_map: textual_object_row.get("card_map"),
+                version: textual_object_row.get("version"),
             };
 
             Some(textual_object)
@@ -156,6 +220,53 @@ pub(crate) async fn delete_to_by_id(pool: &Pool<Sqlite>, id: &Uuid) -> SqliteQue
     delete_query
 }
 
+// update textual object with the same ticket id, only if the stored version equals the expected version.
+// the stored version is incremented; no row is affected when the version has moved on or the ticket id does not exist
+pub(crate) async fn update_to_if_version_matches(pool: &mut PoolConnection<Sqlite>, textual_object: &TextualObject, expected_version: i64) -> SqliteQueryResult {
+    sqlx::query(
+        "UPDATE textual_objects SET
+        ticket_minimal = $1,
+        source_id = $2,
+        source_name = $3,
+        source_id_type = $4,
+        source_path = $5,
+        store_info = $6,
+        store_url = $7,
+        updated = $8,
+        json = $9,
+        card = $10,
+        card_map = $11,
+        version = version + 1
+        WHERE ticket_id = $12 AND version = $13",
+    )
+        .bind(&textual_object.ticket_minimal)
+        .bind(&textual_object.source_id)
+        .bind(&textual_object.source_name)
+        .bind(&textual_object.source_id_type)
+        .bind(&textual_object.source_path)
+        .bind(&textual_object.store_info)
+        .bind(&textual_object.store_url)
+        .bind(textual_object.updated)
+        .bind(&textual_object.json)
+        .bind(&textual_object.card)
+        .bind(&textual_object.card_map)
+        .bind(&textual_object.ticket_id)
+        .bind(expected_version)
+        .execute(pool)
+        .await
+        .unwrap()
+}
+
+// delete textual object by ticket id, only if the stored version equals the expected version
+pub(crate) async fn delete_to_by_ticket_id_if_version_matches(pool: &mut PoolConnection<Sqlite>, ticket_id: &str, expected_version: i64) -> SqliteQueryResult {
+    sqlx::query("DELETE FROM textual_objects WHERE ticket_id = $1 AND version = $2")
+        .bind(ticket_id)
+        .bind(expected_version)
+        .execute(pool)
+        .await
+        .unwrap()
+}
+
 // delete textual object from database by ticket id
 pub(crate) async fn delete_to_by_ticket_id(pool: &mut PoolConnection<Sqlite>, ticket_id: &String) -> SqliteQueryResult {
     let delete_query = sqlx::query("DELETE FROM textual_objects WHERE ticket_id = $1")
@@ -6,12 +6,27 @@ use crate::error::ToErrors;
 pub enum ToErrorMessage {
 
     // give serialize string
-    #[strum(serialize = "No ticket ids provided")]
+    #[strum(serialize = "No ticket ids or source identities provided")]
     FindRequestDtoNoTicketIds,
 
+    #[strum(serialize = "Source identity has an empty source_id")]
+    FindRequestDtoEmptySourceId,
+
     #[strum(serialize = "store_url does not exist")]
     FindOrScanRequestDtoStoreUrlDoesNotExist,
 
     #[strum(serialize = "No text is provided")]
     ScanRequestDtoNoText,
+
+    #[strum(serialize = "No ticket ids, source ids or source name provided")]
+    DeleteRequestDtoNothingSelected,
+
+    #[strum(serialize = "No id remap provided to remap ticket ids")]
+    RewriteRequestDtoNoIdRemap,
+
+    #[strum(serialize = "No textual object with this ticket id")]
+    TicketIdNotFound,
+
+    #[strum(serialize = "The stored textual object has been changed by someone else")]
+    VersionConflict,
 }
@@ -45,6 +45,13 @@ impl ToErrors {
 pub enum ToErrors {
     AddManyRequestError(TextualObjectErrorMessage),
     FindRequestError(TextualObjectErrorMessage),
+    DeleteManyRequestError(TextualObjectErrorMessage),
+    NotFoundError(TextualObjectErrorMessage),
+    ParseError(TextualObjectErrorMessage),
+    // the stored TO has a newer version than expected, the current stored TO is in `payload_for_user`
+    ConflictError(TextualObjectErrorMessage),
+    // a file could not be read or written, e.g. a template file
+    IoError(TextualObjectErrorMessage),
 }
 
 
@@ -55,6 +62,11 @@ impl fmt::Display for ToErrors {
         match self {
             ToErrors::AddManyRequestError(message) => write!(f, "{:?}", message),
             ToErrors::FindRequestError(message) => write!(f, "{:?}", message),
+            ToErrors::DeleteManyRequestError(message) => write!(f, "{:?}", message),
+            ToErrors::NotFoundError(message) => write!(f, "{:?}", message),
+            ToErrors::ParseError(message) => write!(f, "{:?}", message),
+            ToErrors::ConflictError(message) => write!(f, "{:?}", message),
+            ToErrors::IoError(message) => write!(f, "{:?}", message),
         }
     }
 }
@@ -64,6 +76,11 @@ impl fmt::Display for ToErrors {
          match error {
              ToErrors::AddManyRequestError(_) => String::from("Add Many Request DTO Error"),
                 ToErrors::FindRequestError(_) => String::from("Find Request DTO Error"),
+                ToErrors::DeleteManyRequestError(_) => String::from("Delete Many Request DTO Error"),
+                ToErrors::NotFoundError(_) => String::from("Not Found Error"),
+                ToErrors::ParseError(_) => String::from("Parse Error"),
+                ToErrors::ConflictError(_) => String::from("Version Conflict Error"),
+                ToErrors::IoError(_) => String::from("IO Error"),
          }
      }
  }
@@ -21,6 +21,10 @@ unused_imports
 //! - Textual Object Tag
 //!
 //! - Textual Object Card
+//!
+//! - Textual Object Template
+//!
+//! - Textual Object Lint
 
 pub mod entities;
 pub(crate) mod utils;
@@ -33,5 +37,7 @@ pub mod to_card;
 mod to_tag;
 pub mod error;
 pub mod to_parser;
+pub mod to_template;
+pub mod to_lint;
 
 
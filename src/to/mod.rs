@@ -2,4 +2,7 @@ pub mod to_struct;
 
 pub mod to_receipt;
 pub mod to_ticket;
+pub mod to_stale;
+pub mod to_rewrite;
+pub mod to_capture;
 pub mod to_dtos;
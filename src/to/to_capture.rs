@@ -0,0 +1,65 @@
+use crate::to::to_dtos::to_add_dto::ToAddDto;
+use crate::to_parser::parser_option::ToParserOption;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_spec::ToTicketField;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+impl ToTicket {
+    /// whether the ticket describes a TO that is not stored yet: it has values but no id was written,
+    /// e.g. `[[title: Some Book | author: X]]`; the parser gives such tickets a random id that is never stored
+    pub fn is_capturable(&self, opt: &ToParserOption) -> bool {
+        if self.to_dialect == ToMarkerDialect::MarkdownLink || self.values.is_empty() {
+            return false;
+        }
+        let written = self.written_fields(opt);
+        !written.is_empty() && !written.contains(&Some(ToTicketField::Id))
+    }
+
+    /// the TO described by the values of the ticket
+    pub fn to_add_dto(&self, source_name: &str) -> ToAddDto {
+        ToAddDto {
+            source_name: source_name.to_string(),
+            json: serde_json::Value::Object(self.values.iter().map(|(key, value)| (key.clone(), value.to_json())).collect()),
+            ..Default::default()
+        }
+    }
+
+    /// the ticket as written, with the id of its new TO first, e.g. `[[id: abc12 | title: Some Book | author: X]]`
+    pub fn print_captured(&self, ticket_id: &str, opt: &ToParserOption) -> String {
+        let mut captured = self.clone();
+        captured.ticket_id = ticket_id.to_string();
+        if let Some(marker) = self.to_dialect.marker(&opt.to_marker) {
+            captured.to_marker = marker;
+        }
+        let written = self.written_fields(opt);
+        captured.print(Some(self.written_print_option(&written, opt)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::to_parser::parser::ToParser;
+
+    use super::*;
+
+    #[test]
+    fn test_capture() {
+        let opt = ToParserOption {
+            dialects: ToMarkerDialect::all(),
+            ..Default::default()
+        };
+        let text = "[[title:Some Book|author:X|year:1999]] [[id:abc]] [[]] {{title:Other}} [Book](to:abc) [[title:Dated|updated:2020-01-01 00:00:00]]";
+        let tickets = ToParser::scan_text_for_tickets(text, opt.clone()).tickets;
+        let capturable: Vec<bool> = tickets.iter().map(|ticket| ticket.is_capturable(&opt)).collect();
+        assert_eq!(capturable, vec![true, false, false, true, false, true]);
+
+        let dto = tickets[0].to_add_dto("Draft");
+        assert_eq!(dto.source_name, "Draft");
+        assert_eq!(dto.json, json!({"title": "Some Book", "author": "X", "year": 1999}));
+        assert_eq!(tickets[0].print_captured("new12", &opt), "[[id: new12 | title: Some Book | author: X | year: 1999]]");
+        assert_eq!(tickets[3].print_captured("new34", &opt), "{{id: new34 | title: Other}}");
+        assert_eq!(tickets[5].print_captured("new56", &opt), "[[id: new56 | title: Dated | updated: 2020-01-01 00:00:00]]");
+    }
+}
@@ -1,3 +1,8 @@
 pub mod to_add_dto;
+pub mod to_delete_dto;
 pub mod to_find_dto;
 pub mod to_scan_dto;
+pub mod to_rewrite_dto;
+pub mod to_capture_dto;
+pub mod to_lint_dto;
+pub mod to_stats_dto;
@@ -219,6 +219,8 @@ impl From<ToAddDto> for TextualObject {
             card_map: String::new(),
             card: sqlx::types::Json(ToCard::default()),
 
+            version: 1,
+
             json: sqlx::types::Json(dto.json),
         };
         to.update_minimal_ticket()
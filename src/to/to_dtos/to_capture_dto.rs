@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::error::error_message::ToErrorMessage;
+use crate::to::to_dtos::to_add_dto::TextualObjectStoredReceipt;
+use crate::to::to_dtos::to_rewrite_dto::ToTicketRewrite;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::utils::check_if_file_exists::check_if_file_exists;
+
+/// Dto for capturing the tickets of a text that have no id, e.g. `[[title: Some Book | author: X]]`, as new TOs.
+/// In markdown mode `[[...]]` without id are links to notes, so only tickets of other dialects are captured.
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToCaptureRequestDto {
+    pub store_url: String,
+    /// the text to capture from
+    pub text: String,
+    /// source name of the new TOs
+    #[serde(default = "default_source_name")]
+    pub source_name: String,
+    /// write back minimal tickets, e.g. [[id: abc12]], rather than the tickets as written with their new id
+    #[serde(default)]
+    pub write_minimal: bool,
+    /// ways of writing tickets to look for, [[...]] only by default
+    #[serde(default = "default_dialects")]
+    pub dialects: Vec<ToMarkerDialect>,
+    /// read the text as markdown, leaving tickets in code, comments or front matter as they are
+    #[serde(default)]
+    pub markdown: bool,
+}
+
+fn default_source_name() -> String {
+    "Capture".to_string()
+}
+
+fn default_dialects() -> Vec<ToMarkerDialect> {
+    vec![ToMarkerDialect::default()]
+}
+
+impl ToCaptureRequestDto {
+    pub fn validate(&self) -> Result<(), ToErrors> {
+        let mut error_message = TextualObjectErrorMessage::default();
+
+        if self.text.is_empty() {
+            error_message.message = ToErrorMessage::ScanRequestDtoNoText.to_string();
+            return Err(ToErrors::AddManyRequestError(error_message));
+        }
+
+        if !check_if_file_exists(&self.store_url) {
+            error_message.message = ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string();
+            return Err(ToErrors::AddManyRequestError(error_message));
+        }
+
+        Ok(())
+    }
+}
+
+/// The text with the ids of the captured TOs written into their tickets
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToCaptureResultDto {
+    pub store_url: String,
+    pub text: String,
+    // unified diff from the original text, empty when nothing was captured
+    pub diff: String,
+    // the captured tickets, in order of the text; the same ticket written twice is captured once
+    pub captured: Vec<ToTicketRewrite>,
+    // receipt of the new TOs, None when nothing was captured
+    pub receipt: Option<TextualObjectStoredReceipt>,
+}
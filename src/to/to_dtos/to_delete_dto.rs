@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::error::error_message::ToErrorMessage;
+use crate::utils::check_if_file_exists::check_if_file_exists;
+
+/// Dto for deleting many TOs at once.
+/// TOs are selected by ticket ids, by source ids or by source name; when both source ids and source name are given, only source ids of that source are selected.
+#[derive(Clone, Debug, Default, Serialize, ToSchema, Deserialize)]
+pub struct ToDeleteManyDto {
+    pub store_url: String,
+    #[serde(default)]
+    pub ticket_ids: Vec<String>,
+    #[serde(default)]
+    pub source_ids: Vec<String>,
+    #[serde(default)]
+    pub source_name: Option<String>,
+    // ticket ids that must be kept even if they are selected, e.g. TOs still cited somewhere
+    #[serde(default)]
+    pub protected_ticket_ids: Vec<String>,
+}
+
+impl ToDeleteManyDto {
+    pub fn validate(&self) -> Result<(), ToErrors> {
+        let mut error_message = TextualObjectErrorMessage::default();
+        // check whether anything is selected and whether store_url has file
+        let has_source_name = self.source_name.as_ref().map(|name| !name.is_empty()).unwrap_or(false);
+        if self.ticket_ids.is_empty() && self.source_ids.is_empty() && !has_source_name {
+            error_message.message = ToErrorMessage::DeleteRequestDtoNothingSelected.to_string();
+            error_message.suggestion = "Provide \"ticket_ids\", \"source_ids\" or \"source_name\" in your request.".to_string();
+            return Err(ToErrors::DeleteManyRequestError(error_message));
+        }
+
+        if !check_if_file_exists(&self.store_url) {
+            error_message.message = ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string();
+            return Err(ToErrors::DeleteManyRequestError(error_message));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct TextualObjectDeletedReceipt {
+    pub store_url: String,
+    pub deleted_ticket_ids: Vec<String>,
+    // requested ticket ids that are not in the store
+    pub not_found_ticket_ids: Vec<String>,
+    // requested source ids that match no TO in the store
+    pub not_found_source_ids: Vec<String>,
+    // selected ticket ids that have been kept because they are protected
+    pub protected_ticket_ids: Vec<String>,
+    pub total_tos_deleted: usize,
+}
+
+impl TextualObjectDeletedReceipt {
+    pub fn new(store_url: &str) -> Self {
+        TextualObjectDeletedReceipt {
+            store_url: store_url.to_string(),
+            deleted_ticket_ids: Vec::new(),
+            not_found_ticket_ids: Vec::new(),
+            not_found_source_ids: Vec::new(),
+            protected_ticket_ids: Vec::new(),
+            total_tos_deleted: 0,
+        }
+    }
+}
+
+// test
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delete_request_validate_nothing_selected() {
+        let dto = ToDeleteManyDto {
+            store_url: "store_url".to_string(),
+            source_name: Some(String::new()),
+            ..Default::default()
+        };
+        match dto.validate() {
+            Err(ToErrors::DeleteManyRequestError(e)) => {
+                assert_eq!(e.message, ToErrorMessage::DeleteRequestDtoNothingSelected.to_string());
+            }
+            _ => panic!("Expected error"),
+        }
+    }
+
+    #[test]
+    fn test_delete_request_validate_missing_store() {
+        let dto = ToDeleteManyDto {
+            store_url: "store_url".to_string(),
+            ticket_ids: vec!["ticket_id_1".to_string()],
+            ..Default::default()
+        };
+        match dto.validate() {
+            Err(ToErrors::DeleteManyRequestError(e)) => {
+                assert_eq!(e.message, ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string());
+            }
+            _ => panic!("Expected error"),
+        }
+    }
+}
@@ -11,7 +11,28 @@ use crate::utils::check_if_file_exists::check_if_file_exists;
 pub struct ToFindRequestDto {
     pub store_url: String,
     // if this is provided, the store_filename and directory will be ignored.
-    pub ticket_ids: Vec<String>
+    pub ticket_ids: Vec<String>,
+    // identities of TOs in their original sources, e.g. a DOI or a Zotero citekey, for clients that do not know the ticket ids
+    #[serde(default)]
+    pub source_identities: Vec<ToSourceIdentity>,
+}
+
+/// Identity of a TO in its original source.
+/// `source_name` and `source_id_type` are optional, e.g. a bare DOI matches the DOI in whatever source it was added from.
+#[derive(Clone, Debug, PartialEq, Serialize, ToSchema, Deserialize)]
+pub struct ToSourceIdentity {
+    #[serde(default)]
+    pub source_name: Option<String>,
+    #[serde(default)]
+    pub source_id_type: Option<String>,
+    pub source_id: String,
+}
+
+/// Ticket ids of the TOs matching a source identity.
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToSourceIdentityMatch {
+    pub source_identity: ToSourceIdentity,
+    pub ticket_ids: Vec<String>,
 }
 
 impl ToFindRequestDto {
@@ -20,11 +41,16 @@ impl ToFindRequestDto {
         // check whether ticket ids are provided and whether store_url has file
 
 
-        if self.ticket_ids.is_empty() {
+        if self.ticket_ids.is_empty() && self.source_identities.is_empty() {
             error_message.message = ToErrorMessage::FindRequestDtoNoTicketIds.to_string();
             return Err(ToErrors::FindRequestError(error_message));
         }
 
+        if self.source_identities.iter().any(|identity| identity.source_id.is_empty()) {
+            error_message.message = ToErrorMessage::FindRequestDtoEmptySourceId.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
         if !check_if_file_exists(&self.store_url) {
             error_message.message = ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string();
             return Err(ToErrors::FindRequestError(error_message));
@@ -43,6 +69,13 @@ pub struct ToFindResultDto {
     pub found_tos_count: usize,
     pub missing_tos_ids: Vec<String>,
     pub missing_tos_count: usize,
+    // source identities with the ticket ids they resolved to
+    #[serde(default)]
+    pub found_source_identities: Vec<ToSourceIdentityMatch>,
+    #[serde(default)]
+    pub missing_source_identities: Vec<ToSourceIdentity>,
+    #[serde(default)]
+    pub missing_source_identities_count: usize,
 }
 
 // test
@@ -56,9 +89,38 @@ mod test {
         let to_find_request_dto = ToFindRequestDto {
             store_url: "store_url".to_string(),
             ticket_ids: vec!["ticket_id_1".to_string(), "ticket_id_2".to_string()],
+            source_identities: vec![],
         };
         assert!(to_find_request_dto.validate().is_err());
     }
+
+    // test find request with source identities but without ticket ids
+    #[test]
+    fn test_find_request_validate_source_identities() {
+        let no_selector = ToFindRequestDto {
+            store_url: "store_url".to_string(),
+            ticket_ids: vec![],
+            source_identities: vec![],
+        };
+        match no_selector.validate() {
+            Err(ToErrors::FindRequestError(e)) => assert_eq!(e.message, ToErrorMessage::FindRequestDtoNoTicketIds.to_string()),
+            _ => panic!("Expected error"),
+        }
+
+        let empty_source_id = ToFindRequestDto {
+            store_url: "store_url".to_string(),
+            ticket_ids: vec![],
+            source_identities: vec![ToSourceIdentity {
+                source_name: None,
+                source_id_type: Some("DOI".to_string()),
+                source_id: String::new(),
+            }],
+        };
+        match empty_source_id.validate() {
+            Err(ToErrors::FindRequestError(e)) => assert_eq!(e.message, ToErrorMessage::FindRequestDtoEmptySourceId.to_string()),
+            _ => panic!("Expected error"),
+        }
+    }
 }
 
 
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::error::error_message::ToErrorMessage;
+use crate::to_lint::to_lint_rule::{ToLintRule, ToLintSeverity};
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::utils::check_if_file_exists::check_if_file_exists;
+
+/// Dto for linting the tickets of a text
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToLintRequestDto {
+    pub store_url: String,
+    /// the text to lint
+    pub text: String,
+    /// name of the linted file, e.g. `notes/chapter1.md`, reported as the artifact of the findings
+    #[serde(default = "default_source")]
+    pub source: String,
+    /// ways of writing tickets to look for, [[...]] only by default
+    #[serde(default = "default_dialects")]
+    pub dialects: Vec<ToMarkerDialect>,
+    /// read the text as markdown, leaving code, comments, front matter and wiki-links alone
+    #[serde(default)]
+    pub markdown: bool,
+}
+
+fn default_source() -> String {
+    "text".to_string()
+}
+
+fn default_dialects() -> Vec<ToMarkerDialect> {
+    vec![ToMarkerDialect::default()]
+}
+
+impl ToLintRequestDto {
+    pub fn validate(&self) -> Result<(), ToErrors> {
+        let mut error_message = TextualObjectErrorMessage::default();
+
+        if self.text.is_empty() {
+            error_message.message = ToErrorMessage::ScanRequestDtoNoText.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
+        if !check_if_file_exists(&self.store_url) {
+            error_message.message = ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
+        Ok(())
+    }
+}
+
+/// Suggested fix of a finding
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+pub struct ToLintFix {
+    pub description: String,
+    // text to write in place of the raw text of the finding, None when the fix cannot be made without the writer
+    pub replacement: Option<String>,
+}
+
+/// A problem found by the linter
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+pub struct ToLintFinding {
+    // stable code of the rule, e.g. TO001
+    pub code: String,
+    pub rule: ToLintRule,
+    pub severity: ToLintSeverity,
+    pub message: String,
+    // id of the ticket the finding is about, if it has one
+    pub ticket_id: Option<String>,
+    // lines count from 0, columns are in chars; the end is exclusive
+    pub line: usize,
+    pub char_column: usize,
+    pub end_line: usize,
+    pub end_char_column: usize,
+    pub byte_offset: usize,
+    pub end_byte_offset: usize,
+    pub raw_text: String,
+    pub fix: Option<ToLintFix>,
+}
+
+impl ToLintFinding {
+    /// finding of a rule at a position of the text, with the default severity of the rule
+    pub fn new(rule: ToLintRule, message: String, position: &ToTicketPositionInfo) -> Self {
+        ToLintFinding {
+            code: rule.code().to_string(),
+            rule,
+            severity: rule.severity(),
+            message,
+            ticket_id: None,
+            line: position.line,
+            char_column: position.char_column,
+            end_line: position.end_line,
+            end_char_column: position.end_char_column,
+            byte_offset: position.byte_offset,
+            end_byte_offset: position.end_byte_offset,
+            raw_text: position.raw_text.clone(),
+            fix: None,
+        }
+    }
+}
+
+/// Findings of a linted text, in order of the text; see `to_json` and `to_sarif` for machine-readable reports
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToLintReportDto {
+    pub store_url: String,
+    pub source: String,
+    pub findings: Vec<ToLintFinding>,
+}
+
+impl ToLintReportDto {
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|finding| finding.severity == ToLintSeverity::Error)
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::error::error_message::ToErrorMessage;
+use crate::to::to_dtos::to_scan_dto::ToTicketOccurrence;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_projection::ToTicketProjection;
+use crate::utils::check_if_file_exists::check_if_file_exists;
+
+/// Dto for rewriting every ticket of a text.
+/// Tickets that the mode does not change are kept exactly as written.
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToRewriteRequestDto {
+    pub store_url: String,
+    /// the text to rewrite
+    pub text: String,
+    pub mode: ToRewriteMode,
+    /// old ticket id to new ticket id, for the remap mode
+    #[serde(default)]
+    pub id_remap: HashMap<String, String>,
+    /// fields of the TO put in upgraded tickets, every field by default
+    #[serde(default)]
+    pub projection: ToTicketProjection,
+    /// ways of writing tickets to look for, [[...]] only by default
+    #[serde(default = "default_dialects")]
+    pub dialects: Vec<ToMarkerDialect>,
+    /// read the text as markdown, leaving tickets in code, comments or front matter as they are
+    #[serde(default)]
+    pub markdown: bool,
+}
+
+fn default_dialects() -> Vec<ToMarkerDialect> {
+    vec![ToMarkerDialect::default()]
+}
+
+/// How tickets are rewritten
+#[derive(Clone, Copy, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToRewriteMode {
+    // replace minimal tickets with full tickets printed from their TO
+    Upgrade,
+    // replace full tickets with minimal ones, e.g. [[id: abc]]
+    Downgrade,
+    // update the dates, values and labels of tickets that are out of date with their TO
+    Refresh,
+    // replace ticket ids found in id_remap with their new id
+    Remap,
+}
+
+impl ToRewriteMode {
+    /// whether the mode reads the TOs of the tickets from the store
+    pub fn reads_store(&self) -> bool {
+        matches!(self, ToRewriteMode::Upgrade | ToRewriteMode::Refresh)
+    }
+}
+
+impl ToRewriteRequestDto {
+    pub fn validate(&self) -> Result<(), ToErrors> {
+        let mut error_message = TextualObjectErrorMessage::default();
+
+        if self.text.is_empty() {
+            error_message.message = ToErrorMessage::ScanRequestDtoNoText.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
+        if self.mode == ToRewriteMode::Remap && self.id_remap.is_empty() {
+            error_message.message = ToErrorMessage::RewriteRequestDtoNoIdRemap.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
+        if self.mode.reads_store() && !check_if_file_exists(&self.store_url) {
+            error_message.message = ToErrorMessage::FindOrScanRequestDtoStoreUrlDoesNotExist.to_string();
+            return Err(ToErrors::FindRequestError(error_message));
+        }
+
+        Ok(())
+    }
+}
+
+/// The rewritten text, with a unified diff from the original text
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToRewriteResultDto {
+    pub store_url: String,
+    pub text: String,
+    // empty when no ticket was rewritten
+    pub diff: String,
+    // the rewritten tickets, in order of the text
+    pub rewritten: Vec<ToTicketRewrite>,
+    pub rewritten_count: usize,
+    // ticket ids without TO in the store, whose tickets were kept as written
+    pub missing_tos_ids: Vec<String>,
+}
+
+/// A ticket of the original text and what it was rewritten to
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToTicketRewrite {
+    pub ticket_id: String,
+    pub occurrence: ToTicketOccurrence,
+    pub new_text: String,
+}
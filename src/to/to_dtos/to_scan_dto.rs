@@ -1,5 +1,8 @@
 use std::collections::HashMap;
+use indexmap::IndexMap;
 use crate::to::to_struct::TextualObject;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_struct::ToTicket;
 use utoipa::ToSchema;
 use serde::{Deserialize, Serialize};
 use crate::error::{TextualObjectErrorMessage, ToErrors};
@@ -11,7 +14,36 @@ use crate::utils::check_if_file_exists::check_if_file_exists;
 pub struct ToScanRequestDto {
     pub store_url: String,
     /// the text to scan from
-    pub text: String
+    pub text: String,
+    /// how tickets are removed from the text in the cleaned text of the result
+    #[serde(default)]
+    pub clean_mode: ToCleanMode,
+    /// ways of writing tickets to look for, [[...]] only by default
+    #[serde(default = "default_dialects")]
+    pub dialects: Vec<ToMarkerDialect>,
+    /// read the text as markdown, only finding tickets in prose rather than in code, comments or front matter
+    #[serde(default)]
+    pub markdown: bool,
+    /// compare the tickets with the stored TOs and report those that are out of date
+    #[serde(default)]
+    pub check_stale: bool,
+}
+
+fn default_dialects() -> Vec<ToMarkerDialect> {
+    vec![ToMarkerDialect::default()]
+}
+
+/// How ticket spans are cleaned from a scanned text
+#[derive(Clone, Copy, Debug, Default, Serialize, ToSchema, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToCleanMode {
+    // remove the ticket, with the spaces before it when it stands between words
+    #[default]
+    Strip,
+    // replace the ticket with its minimal form, e.g. [[id: abc]]; a ticket written without id is left as written
+    ReplaceWithMinimalTicket,
+    // replace the ticket with a readable label, e.g. the title of its TO
+    ReplaceWithLabel,
 }
 
 impl ToScanRequestDto {
@@ -38,11 +70,111 @@ impl ToScanRequestDto {
 #[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
 pub struct ToScanResultDto {
     pub store_url: String,
-    // HashMap<ticket_id, TextualObject>
+    // each TO once, in order of first occurrence in the text
     pub found_tos: Vec<TextualObject>,
     pub found_tos_count: usize,
     pub missing_tos_ids: Vec<String>,
     pub missing_tos_count: usize,
     pub cleaned_text: String,
+    // segments of the cleaned text with the range of the original text they come from, in order
+    #[serde(default)]
+    pub source_map: Vec<ToSourceMapSegment>,
+    // where the tickets of each found TO occur, in the order of found_tos
+    #[serde(default)]
+    pub found_to_occurrences: Vec<ToScanTicketOccurrences>,
+    // where the tickets of each missing id occur, in the order of missing_tos_ids
+    #[serde(default)]
+    pub missing_to_occurrences: Vec<ToScanTicketOccurrences>,
+    // tickets out of date with their stored TO, in order of the text; only when check_stale is requested
+    #[serde(default)]
+    pub stale_tickets: Vec<ToStaleTicket>,
+}
+
+/// A ticket in a scanned text that is out of date with its stored TO
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToStaleTicket {
+    pub ticket_id: String,
+    pub occurrence: ToTicketOccurrence,
+    pub differences: Vec<ToFieldDifference>,
+}
+
+/// A field written differently in a ticket and in its stored TO; None where the field is not written
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+pub struct ToFieldDifference {
+    pub field: String,
+    pub in_ticket: Option<serde_json::Value>,
+    pub in_store: Option<serde_json::Value>,
+}
+
+/// All occurrences of a ticket id in a scanned text
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToScanTicketOccurrences {
+    pub ticket_id: String,
+    pub occurrences: Vec<ToTicketOccurrence>,
+}
+
+/// One ticket in a scanned text: where it is, lines and columns from 0 and in bytes, and the values written in it
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+pub struct ToTicketOccurrence {
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub byte_offset: usize,
+    pub end_byte_offset: usize,
+    pub raw_text: String,
+    // values other than the id, e.g. a page number, as json
+    pub values: IndexMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub dialect: ToMarkerDialect,
+    // display label written with the ticket, which may be out of date
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+impl From<&ToTicket> for ToTicketOccurrence {
+    fn from(ticket: &ToTicket) -> Self {
+        let position = ticket.to_intext_option.clone().unwrap_or_default();
+        ToTicketOccurrence {
+            line: position.line,
+            column: position.column,
+            end_line: position.end_line,
+            end_column: position.end_column,
+            byte_offset: position.byte_offset,
+            end_byte_offset: position.end_byte_offset,
+            raw_text: position.raw_text,
+            values: ticket.values.iter().map(|(key, value)| (key.clone(), value.to_json())).collect(),
+            dialect: ticket.to_dialect,
+            label: ticket.to_label.clone(),
+        }
+    }
+}
+
+/// A range of the cleaned text and the range of the original text it comes from, as byte offsets.
+/// Unchanged text has ranges of the same length; a cleaned ticket maps its replacement, possibly empty, to the whole ticket.
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize, PartialEq)]
+pub struct ToSourceMapSegment {
+    pub cleaned_offset: usize,
+    pub cleaned_length: usize,
+    pub original_offset: usize,
+    pub original_length: usize,
+    // ticket id of a cleaned ticket, None for unchanged text
+    pub ticket_id: Option<String>,
+}
+
+impl ToScanResultDto {
+    /// offset in the original text of an offset in the cleaned text.
+    /// An offset inside a replacement maps to the start of the ticket it replaces
+    pub fn original_offset(&self, cleaned_offset: usize) -> usize {
+        // the last segment starting at or before the offset, preferring unchanged text over an empty replacement at the same place
+        let segment = self.source_map.iter()
+            .rfind(|segment| segment.cleaned_offset <= cleaned_offset);
+        match segment {
+            Some(segment) if segment.ticket_id.is_none() => segment.original_offset + (cleaned_offset - segment.cleaned_offset).min(segment.original_length),
+            Some(segment) if cleaned_offset < segment.cleaned_offset + segment.cleaned_length => segment.original_offset,
+            Some(segment) => segment.original_offset + segment.original_length,
+            None => cleaned_offset,
+        }
+    }
 }
 
@@ -0,0 +1,89 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Dto describing the health of a store at a glance.
+#[derive(Clone, Debug, Serialize, ToSchema, Deserialize)]
+pub struct ToStatsDto {
+    pub store_url: String,
+    pub store_info: String,
+    // total number of tos in the store
+    pub to_count: i64,
+    // <source_name, count>
+    pub count_by_source_name: IndexMap<String, i64>,
+    // <source_id_type, count>
+    pub count_by_source_id_type: IndexMap<String, i64>,
+    // <day in YYYY-MM-DD, count>, ordered by day
+    pub created_histogram: IndexMap<String, i64>,
+    // <day in YYYY-MM-DD, count>, ordered by day
+    pub updated_histogram: IndexMap<String, i64>,
+    // average size in bytes of the json field
+    pub average_json_size: f64,
+    // number of tos whose card has a non-empty title
+    pub cards_with_title: i64,
+    // number of tos whose card has at least one creator
+    pub cards_with_creators: i64,
+    // size of the database file in bytes
+    pub database_file_size: u64,
+}
+
+impl Default for ToStatsDto {
+    fn default() -> Self {
+        ToStatsDto {
+            store_url: String::new(),
+            store_info: String::new(),
+            to_count: 0,
+            count_by_source_name: IndexMap::new(),
+            count_by_source_id_type: IndexMap::new(),
+            created_histogram: IndexMap::new(),
+            updated_histogram: IndexMap::new(),
+            average_json_size: 0.0,
+            cards_with_title: 0,
+            cards_with_creators: 0,
+            database_file_size: 0,
+        }
+    }
+}
+
+impl ToStatsDto {
+    // share of tos whose card has a non-empty title, from 0 to 1
+    pub fn card_title_coverage(&self) -> f64 {
+        if self.to_count == 0 {
+            return 0.0;
+        }
+        self.cards_with_title as f64 / self.to_count as f64
+    }
+
+    // share of tos whose card has at least one creator, from 0 to 1
+    pub fn card_creators_coverage(&self) -> f64 {
+        if self.to_count == 0 {
+            return 0.0;
+        }
+        self.cards_with_creators as f64 / self.to_count as f64
+    }
+}
+
+// test
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_coverage_with_empty_store() {
+        let stats = ToStatsDto::default();
+        assert_eq!(stats.card_title_coverage(), 0.0);
+        assert_eq!(stats.card_creators_coverage(), 0.0);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let stats = ToStatsDto {
+            to_count: 4,
+            cards_with_title: 2,
+            cards_with_creators: 1,
+            ..Default::default()
+        };
+        assert_eq!(stats.card_title_coverage(), 0.5);
+        assert_eq!(stats.card_creators_coverage(), 0.25);
+    }
+}
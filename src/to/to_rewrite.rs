@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use chrono::{Offset, TimeZone, Timelike, Utc};
+
+use crate::to::to_dtos::to_rewrite_dto::ToRewriteMode;
+use crate::to::to_struct::TextualObject;
+use crate::to_parser::parser_option::ToParserOption;
+use crate::to_parser::parser_span::ToTicketSpan;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
+use crate::to_ticket::to_ticket_projection::ToTicketProjection;
+use crate::to_ticket::to_ticket_spec::ToTicketField;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+/// What a rewrite needs besides the ticket
+pub struct ToRewriteContext<'a> {
+    pub mode: ToRewriteMode,
+    // the TO of the ticket, if it is in the store
+    pub textual_object: Option<&'a TextualObject>,
+    pub id_remap: &'a HashMap<String, String>,
+    pub projection: &'a ToTicketProjection,
+    // the option the ticket was scanned with
+    pub parser_option: &'a ToParserOption,
+}
+
+impl ToTicket {
+    /// The ticket rewritten as the mode asks, from its position in the scanned text; None when it stays as written.
+    /// Rewritten tickets are printed with the markers they were written with and only the meta-data they had,
+    /// except upgraded tickets, which are printed in full.
+    pub fn rewrite(&self, context: &ToRewriteContext) -> Option<String> {
+        let raw_text = &self.to_intext_option.as_ref()?.raw_text;
+        if self.ticket_id.is_empty() {
+            return None;
+        }
+        let spec = &context.parser_option.ticket_spec;
+        if self.to_dialect == ToMarkerDialect::MarkdownLink {
+            // a link has no values or meta-data, only its id can be rewritten
+            let new_id = match context.mode {
+                ToRewriteMode::Remap => context.id_remap.get(&self.ticket_id)?,
+                _ => return None,
+            };
+            let target = raw_text.rfind("(to:")?;
+            return Some(format!("{}(to:{})", &raw_text[..target], new_id));
+        }
+        let mut ticket = self.clone();
+        ticket.to_marker = self.to_dialect.marker(&context.parser_option.to_marker)?;
+        let written = self.written_fields(context.parser_option);
+        // a ticket written without id has a random one, which refers to no TO
+        if !written.contains(&Some(ToTicketField::Id)) {
+            return None;
+        }
+        let is_minimal = written.iter().all(|field| matches!(field, Some(ToTicketField::Id) | Some(ToTicketField::Label)));
+
+        match context.mode {
+            ToRewriteMode::Upgrade => {
+                let textual_object = context.textual_object?;
+                if !is_minimal {
+                    return None;
+                }
+                let mut full = ToTicket::from_textual_object(textual_object, context.projection);
+                full.to_marker = ticket.to_marker;
+                Some(full.print(Some(ToTicketPrintOption {
+                    // labels are printed where the spec reserves them
+                    include_label: true,
+                    ticket_spec: spec.clone(),
+                    ..Default::default()
+                })))
+            }
+            ToRewriteMode::Downgrade => {
+                if written.iter().all(|field| *field == Some(ToTicketField::Id)) {
+                    return None;
+                }
+                Some(ticket.print(Some(ToTicketPrintOption {
+                    include_label: false,
+                    minimal: true,
+                    ticket_spec: spec.clone(),
+                    ..Default::default()
+                })))
+            }
+            ToRewriteMode::Refresh => {
+                let textual_object = context.textual_object?;
+                let differences = self.stale_differences(textual_object);
+                let mut stored = None;
+                for difference in differences.iter() {
+                    if difference.field == "updated" {
+                        ticket.to_updated = Utc.fix().from_utc_datetime(&textual_object.updated.with_nanosecond(0).unwrap());
+                    } else {
+                        let stored = stored.get_or_insert_with(|| ToTicketProjection::default().project(&textual_object.json.0));
+                        if let Some(value) = stored.get(difference.field.trim()) {
+                            ticket.values.insert(difference.field.clone(), value.clone());
+                        }
+                    }
+                }
+                // the label is refreshed only where one is written
+                let label_changed = ticket.to_label.is_some() && ticket.refresh_label(textual_object);
+                if differences.is_empty() && !label_changed {
+                    return None;
+                }
+                Some(ticket.print(Some(self.written_print_option(&written, context.parser_option))))
+            }
+            ToRewriteMode::Remap => {
+                ticket.ticket_id = context.id_remap.get(&self.ticket_id)?.clone();
+                Some(ticket.print(Some(self.written_print_option(&written, context.parser_option))))
+            }
+        }
+    }
+
+    // whether the id of the ticket was written in the text; the parser gives tickets written without id a random one.
+    // The id is all a markdown link has
+    pub(crate) fn has_written_id(&self, opt: &ToParserOption) -> bool {
+        self.to_dialect == ToMarkerDialect::MarkdownLink || self.written_fields(opt).contains(&Some(ToTicketField::Id))
+    }
+
+    // reserved field of each key written in the ticket, None for values
+    pub(crate) fn written_fields(&self, opt: &ToParserOption) -> Vec<Option<ToTicketField>> {
+        self.written_entries(opt).iter().map(|(key, _)| opt.ticket_spec.field_of(key)).collect()
+    }
+
+    // key and raw text of each entry written in the ticket, in order; empty entries are left out
+    pub(crate) fn written_entries(&self, opt: &ToParserOption) -> Vec<(String, &str)> {
+        let raw_text = match &self.to_intext_option {
+            Some(position) => &position.raw_text,
+            None => return Vec::new(),
+        };
+        let marker = match self.to_dialect.marker(&opt.to_marker) {
+            Some(marker) => marker,
+            None => return Vec::new(),
+        };
+        let content = raw_text.strip_prefix(marker.left_marker.as_str()).unwrap_or(raw_text);
+        let content = content.strip_suffix(marker.right_marker.as_str()).unwrap_or(content);
+        marker.split_unescaped(content, &marker.value_entry_separator, usize::MAX).into_iter()
+            .filter_map(|entry| {
+                let mut pieces = marker.split_unescaped(entry, &marker.value_separator, 2).into_iter();
+                let key = marker.unescape(pieces.next().unwrap_or("").trim());
+                // [[:key]] is read as a key without value
+                let key = if key.is_empty() { marker.unescape(pieces.next().unwrap_or("").trim()) } else { key };
+                if key.is_empty() { None } else { Some((key, entry)) }
+            })
+            .collect()
+    }
+
+    // print only the meta-data written in the ticket
+    pub(crate) fn written_print_option(&self, written: &[Option<ToTicketField>], opt: &ToParserOption) -> ToTicketPrintOption {
+        let has = |field: ToTicketField| written.contains(&Some(field));
+        ToTicketPrintOption {
+            include_updated: has(ToTicketField::Updated),
+            include_created: has(ToTicketField::Created),
+            include_store_info: has(ToTicketField::StoreInfo),
+            include_store_id: has(ToTicketField::StoreUrl),
+            include_label: has(ToTicketField::Label),
+            minimal: false,
+            ticket_spec: opt.ticket_spec.clone(),
+            // rewritten tickets must be read back as tickets
+        }
+    }
+}
+
+impl ToTicketSpan {
+    /// The closing ticket of the span rewritten as the mode asks, None when it stays as written.
+    /// A closing ticket only repeats the id of its opening ticket, so only remapping changes it.
+    pub fn rewrite_close(&self, opening: &ToTicket, context: &ToRewriteContext) -> Option<String> {
+        if context.mode != ToRewriteMode::Remap {
+            return None;
+        }
+        let new_id = context.id_remap.get(&self.ticket_id)?;
+        let marker = opening.to_dialect.marker(&context.parser_option.to_marker)?;
+        Some(format!(
+            "{}/{}: {}{}",
+            marker.left_marker,
+            context.parser_option.ticket_spec.key(ToTicketField::Id),
+            marker.escape_value(new_id),
+            marker.right_marker,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use crate::to_parser::parser::ToParser;
+    use crate::to_ticket::to_ticket_spec::ToTicketSpec;
+
+    use super::*;
+
+    fn stored() -> TextualObject {
+        TextualObject {
+            ticket_id: "abc12".to_string(),
+            updated: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+            created: NaiveDate::from_ymd_opt(2019, 1, 1).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+            json: sqlx::types::Json(json!({"title": "Discipline and Punish", "author": "Michel Foucault", "year": 1975})),
+            ..Default::default()
+        }
+    }
+
+    fn rewrite(text: &str, mode: ToRewriteMode, id_remap: &HashMap<String, String>) -> Vec<Option<String>> {
+        let to = stored();
+        let parser_option = ToParserOption {
+            dialects: ToMarkerDialect::all(),
+            ticket_spec: ToTicketSpec::with_label(),
+            ..Default::default()
+        };
+        let projection = ToTicketProjection::of_paths(&["title"]);
+        let context = ToRewriteContext {
+            mode,
+            textual_object: Some(&to),
+            id_remap,
+            projection: &projection,
+            parser_option: &parser_option,
+        };
+        ToParser::scan_text_for_tickets(text, parser_option.clone()).tickets.iter()
+            .map(|ticket| ticket.rewrite(&context))
+            .collect()
+    }
+
+    #[test]
+    fn test_upgrade_and_downgrade() {
+        let text = "[[id:abc12]] {{id:abc12|label:old}} [[id:abc12|p:12]]";
+        assert_eq!(rewrite(text, ToRewriteMode::Upgrade, &HashMap::new()), vec![
+            Some("[[id: abc12 | title: Discipline and Punish | updated: 2020-01-01 10:00:00 | created: 2019-01-01 10:00:00 | label: Foucault 1975, Discipline and Punish]]".to_string()),
+            Some("{{id: abc12 | title: Discipline and Punish | updated: 2020-01-01 10:00:00 | created: 2019-01-01 10:00:00 | label: Foucault 1975, Discipline and Punish}}".to_string()),
+            None,
+        ]);
+        assert_eq!(rewrite(text, ToRewriteMode::Downgrade, &HashMap::new()), vec![
+            None,
+            Some("{{id: abc12}}".to_string()),
+            Some("[[id: abc12]]".to_string()),
+        ]);
+        // tickets without id are left as written
+        assert_eq!(rewrite("[[title:Draft]]", ToRewriteMode::Downgrade, &HashMap::new()), vec![None]);
+    }
+
+    #[test]
+    fn test_refresh() {
+        let text = "[[id:abc12|title:Old|p:12|updated:2019-06-01 00:00:00]] [[id:abc12|title:Discipline and Punish|label:old]] [[id:abc12|p:3]]";
+        assert_eq!(rewrite(text, ToRewriteMode::Refresh, &HashMap::new()), vec![
+            Some("[[id: abc12 | title: Discipline and Punish | p: 12 | updated: 2020-01-01 10:00:00]]".to_string()),
+            Some("[[id: abc12 | title: Discipline and Punish | label: Foucault 1975, Discipline and Punish]]".to_string()),
+            None,
+        ]);
+    }
+
+    #[test]
+    fn test_remap() {
+        let id_remap = HashMap::from([("abc12".to_string(), "xyz34".to_string())]);
+        let text = "[[id:abc12|p:12]] [Discipline](to:abc12) [[id:other]]";
+        assert_eq!(rewrite(text, ToRewriteMode::Remap, &id_remap), vec![
+            Some("[[id: xyz34 | p: 12]]".to_string()),
+            Some("[Discipline](to:xyz34)".to_string()),
+            None,
+        ]);
+    }
+}
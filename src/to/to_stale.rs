@@ -0,0 +1,122 @@
+use chrono::{TimeZone, Utc};
+
+use crate::to::to_dtos::to_scan_dto::ToFieldDifference;
+use crate::to::to_struct::TextualObject;
+use crate::to_ticket::to_ticket_projection::ToTicketProjection;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+use crate::to_ticket::to_ticket_value::ToTicketValue;
+
+// dates are compared and reported to the second, as tickets print them
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+impl ToTicket {
+    /// Differences between the ticket and the stored TO it refers to, empty if the ticket is up to date:
+    /// an `updated` date older than the TO's, and values that disagree with the JSON of the TO.
+    /// Values the JSON does not have, e.g. a page number, belong to the citation and are not compared;
+    /// neither is the label, which is not authoritative.
+    pub fn stale_differences(&self, textual_object: &TextualObject) -> Vec<ToFieldDifference> {
+        let mut differences = Vec::new();
+        // tickets without an updated date get the current date, so they are never older
+        if self.to_updated.timestamp() < Utc.from_utc_datetime(&textual_object.updated).timestamp() {
+            differences.push(ToFieldDifference {
+                field: "updated".to_string(),
+                in_ticket: Some(self.to_updated.naive_utc().format(DATE_FORMAT).to_string().into()),
+                in_store: Some(textual_object.updated.format(DATE_FORMAT).to_string().into()),
+            });
+        }
+
+        let stored = ToTicketProjection::default().project(&textual_object.json.0);
+        for (key, value) in self.values.iter() {
+            if let Some(stored_value) = stored.get(key.trim()) {
+                if !same_value(value, stored_value) {
+                    differences.push(ToFieldDifference {
+                        field: key.clone(),
+                        in_ticket: Some(value.to_json()),
+                        in_store: Some(stored_value.to_json()),
+                    });
+                }
+            }
+        }
+        differences
+    }
+}
+
+// whether a ticket value agrees with the stored one, type included: `year: 1975` disagrees with a year stored as a string.
+// JSON has no dates, so a date agrees with the string of the same date
+fn same_value(value: &ToTicketValue, stored_value: &ToTicketValue) -> bool {
+    match (value, stored_value) {
+        (ToTicketValue::Date(date), ToTicketValue::String(text)) => date.to_string() == *text,
+        (ToTicketValue::List(items), ToTicketValue::List(stored_items)) => {
+            items.len() == stored_items.len() && items.iter().zip(stored_items).all(|(item, stored_item)| same_value(item, stored_item))
+        }
+        _ => value == stored_value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    use crate::to_parser::parser_option::ToParserOption;
+
+    use super::*;
+
+    fn stored() -> TextualObject {
+        TextualObject {
+            ticket_id: "abc12".to_string(),
+            updated: NaiveDate::from_ymd_opt(2020, 1, 1).unwrap().and_hms_opt(10, 0, 0).unwrap(),
+            json: sqlx::types::Json(json!({"title": "Discipline and Punish", "year": 1975, "date": "1975-02-01", "tags": ["prison", "power"], "author": {"name": "Foucault"}})),
+            ..Default::default()
+        }
+    }
+
+    fn parse(text: &str) -> ToTicket {
+        ToTicket::parse(text, &ToParserOption::default(), None).0
+    }
+
+    #[test]
+    fn test_up_to_date() {
+        let to = stored();
+        assert!(parse("[[id: abc12]]").stale_differences(&to).is_empty());
+        assert!(parse("[[id: abc12 | year: 1975 | page: 12 | author.name: Foucault | updated: 2020-01-01 10:00:00]]").stale_differences(&to).is_empty());
+        assert!(parse("[[id: abc12 | date: 1975-02-01 | tags: prison | tags: power]]").stale_differences(&to).is_empty());
+    }
+
+    // a value of another type than the stored one is stale, even when it reads the same
+    #[test]
+    fn test_stale_type() {
+        let to = stored();
+        let differences = parse("[[id: abc12 | year: \"1975\" | tags: prison, power]]").stale_differences(&to);
+        assert_eq!(differences, vec![
+            ToFieldDifference {
+                field: "year".to_string(),
+                in_ticket: Some(json!("1975")),
+                in_store: Some(json!(1975)),
+            },
+            ToFieldDifference {
+                field: "tags".to_string(),
+                in_ticket: Some(json!("prison, power")),
+                in_store: Some(json!(["prison", "power"])),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_stale() {
+        let to = stored();
+        let differences = parse("[[id: abc12 | title: Surveiller et punir | page: 12 | updated: 2019-06-01 00:00:00]]").stale_differences(&to);
+        assert_eq!(differences, vec![
+            ToFieldDifference {
+                field: "updated".to_string(),
+                in_ticket: Some(json!("2019-06-01 00:00:00")),
+                in_store: Some(json!("2020-01-01 10:00:00")),
+            },
+            ToFieldDifference {
+                field: "title".to_string(),
+                in_ticket: Some(json!("Surveiller et punir")),
+                in_store: Some(json!("Discipline and Punish")),
+            },
+        ]);
+    }
+}
@@ -1,11 +1,11 @@
-use chrono::{FixedOffset, TimeZone, Utc};
-use indexmap::IndexMap;
+use chrono::{FixedOffset, Offset, TimeZone, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::to_card::to_card_struct::ToCard;
 use crate::to_ticket::to_ticket_struct::ToTicket;
 use crate::to_ticket::to_ticket_utils::print_minimal_ticket;
+use crate::to_ticket::to_ticket_projection::ToTicketProjection;
 use crate::utils::id_generator::generate_id;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -38,6 +38,15 @@ pub struct TextualObject {
 
     // map of string to string, format: "to_key1, card_key1; to_key2, card_key2;" etc.
     pub card_map: String,
+
+    // incremented on every update, used to detect concurrent writes
+    #[serde(default = "first_version")]
+    pub version: i64,
+}
+
+// version of a textual object that has never been updated, also given to serialized objects written before versions
+fn first_version() -> i64 {
+    1
 }
 
 // implement default values for textual object
@@ -59,6 +68,7 @@ impl Default for TextualObject {
             json: sqlx::types::Json(serde_json::Value::Null),
             card: sqlx::types::Json(ToCard::default()),
             card_map: String::new(),
+            version: 1,
         }
     }
 }
@@ -83,6 +93,7 @@ impl TextualObject {
             json: sqlx::types::Json(serde_json::Value::Null),
             card: sqlx::types::Json(ToCard::default()),
             card_map: String::new(),
+            version: 1,
         }
     }
 }
@@ -111,15 +122,17 @@ impl TextualObject {
     }
 }
 
-// implement converter from textual object to TextualObjectTicket
+// implement converter from textual object to TextualObjectTicket, projecting every field of the JSON
 impl From<TextualObject> for ToTicket {
     fn from(textual_object: TextualObject) -> ToTicket {
-        // convert textual_object.json to IndexMap
-        let json = &textual_object.json.0;
-        let mut index_map: IndexMap<String, String> = IndexMap::new();
-        for (key, value) in json.as_object().unwrap().iter() {
-            index_map.insert(key.to_string(), value.to_string());
-        }
+        ToTicket::from_textual_object(&textual_object, &ToTicketProjection::default())
+    }
+}
+
+impl ToTicket {
+    /// ticket of a textual object, with the fields of its JSON chosen by the projection
+    pub fn from_textual_object(textual_object: &TextualObject, projection: &ToTicketProjection) -> ToTicket {
+        let index_map = projection.project(&textual_object.json.0);
 
         // if length > 0, then assign the value
         let store_url = if !textual_object.store_url.is_empty()  {
@@ -136,11 +149,14 @@ impl From<TextualObject> for ToTicket {
             id: String::new(),
             ticket_id: textual_object.ticket_id.clone(),
             values: index_map,
-            to_updated: FixedOffset::east(0).timestamp(textual_object.updated.timestamp(), 0),
+            to_updated: Utc.fix().from_utc_datetime(&textual_object.updated.with_nanosecond(0).unwrap()),
+            to_created: Some(Utc.fix().from_utc_datetime(&textual_object.created.with_nanosecond(0).unwrap())),
             to_store_url: store_url,
             to_store_info: store_info,
+            to_label: textual_object.display_label(),
             to_marker: Default::default(),
             to_intext_option: None,
+            to_dialect: Default::default(),
         }
     }
 }
@@ -150,6 +166,7 @@ impl From<TextualObject> for ToTicket {
 mod test {
     use uuid::Uuid;
 
+    use crate::to_ticket::to_ticket_projection::ToTicketProjection;
     use crate::to_ticket::to_ticket_struct::ToTicket;
 
     // test get_sample
@@ -171,6 +188,23 @@ mod test {
 
         let ticket = textual_object_ticket.print(None);
         assert!(ticket.len() > 0);
+        // strings are not quoted and nested objects are flattened
+        assert!(ticket.contains("| test_string: test_string_value |"));
+        assert!(ticket.contains("| test_object.test_number: 1 |"));
+        assert!(!ticket.contains("test_null"));
 
     }
+
+    // a TO without JSON object has a ticket without values
+    #[test]
+    fn default_textual_object_ticket_test() {
+        let textual_object = super::TextualObject::default();
+        let textual_object_ticket = ToTicket::from(textual_object.clone());
+        assert!(textual_object_ticket.values.is_empty());
+
+        let projection = ToTicketProjection::of_paths(&["test_object.test_string"]);
+        let ticket = super::TextualObject::get_sample().print_ticket(&projection, None);
+        assert!(ticket.contains("| test_object.test_string: test_string_value |"));
+        assert!(!ticket.contains("test_number"));
+    }
 }
@@ -1,6 +1,8 @@
 // implement print minimal ticket for TextualObjectMachine
 
 use crate::to::to_struct::TextualObject;
+use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
+use crate::to_ticket::to_ticket_projection::ToTicketProjection;
 use crate::to_ticket::to_ticket_struct::ToTicket;
 
 impl TextualObject {
@@ -9,6 +11,11 @@ impl TextualObject {
         ticket.print_minimal()
     }
 
+    /// print a full ticket with the fields of the JSON chosen by the projection
+    pub fn print_ticket(&self, projection: &ToTicketProjection, opt: Option<ToTicketPrintOption>) -> String {
+        ToTicket::from_textual_object(self, projection).print(opt)
+    }
+
     pub fn update_minimal_ticket(&mut self) -> Self {
         let ticket = ToTicket::from(self.clone());
         let ticket_minimal = ticket.print_minimal();
@@ -17,3 +24,139 @@ impl TextualObject {
         self.to_owned()
     }
 }
+
+// display labels, e.g. "Foucault 1975, Discipline and Punish"
+impl TextualObject {
+    /// print the id with the display label, for readers of the plain text
+    pub fn print_labelled_ticket(&self) -> String {
+        ToTicket::from(self.clone()).print_labelled()
+    }
+
+    /// readable label from the card, or from the `title`, `author` and `year` or `date` of the JSON; None if none is known
+    pub fn display_label(&self) -> Option<String> {
+        let card = &self.card.0;
+        let json = &self.json.0;
+
+        let mut names: Vec<String> = card.creators().values().map(|person| family_name(&person.name)).filter(|name| !name.is_empty()).collect();
+        if names.is_empty() {
+            names = match json.get("author") {
+                Some(serde_json::Value::String(author)) => vec![family_name(author)],
+                Some(serde_json::Value::Array(authors)) => authors.iter().filter_map(|author| author.as_str()).map(family_name).collect(),
+                _ => Vec::new(),
+            };
+            names.retain(|name| !name.is_empty());
+        }
+        let authors = match names.len() {
+            0 => String::new(),
+            1 => names[0].clone(),
+            2 => format!("{} & {}", names[0], names[1]),
+            _ => format!("{} et al.", names[0]),
+        };
+
+        // the year, or the leading year of a date such as 1975-02-01
+        let year = match json.get("year").or_else(|| json.get("date")) {
+            Some(serde_json::Value::Number(year)) => year.to_string(),
+            Some(serde_json::Value::String(date)) => date.chars().take_while(|c| c.is_ascii_digit()).collect(),
+            _ => String::new(),
+        };
+
+        let title = Some(card.title().trim())
+            .filter(|title| !title.is_empty())
+            .or_else(|| json.get("title").and_then(|title| title.as_str()).map(|title| title.trim()))
+            .unwrap_or("");
+
+        let byline = format!("{} {}", authors, year).trim().to_string();
+        let label = match (byline.is_empty(), title.is_empty()) {
+            (true, true) => return None,
+            (false, true) => byline,
+            (true, false) => title.to_string(),
+            (false, false) => format!("{}, {}", byline, title),
+        };
+        Some(label)
+    }
+}
+
+impl ToTicket {
+    /// refresh the display label from the TO the ticket refers to; returns whether the label changed
+    pub fn refresh_label(&mut self, textual_object: &TextualObject) -> bool {
+        let label = textual_object.display_label();
+        if self.to_label == label {
+            return false;
+        }
+        self.to_label = label;
+        true
+    }
+}
+
+// family name of a person, written "Michel Foucault" or "Foucault, Michel"
+fn family_name(name: &str) -> String {
+    let name = name.trim();
+    match name.split_once(',') {
+        Some((family, _)) => family.trim().to_string(),
+        None => name.split_whitespace().last().unwrap_or("").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::to::to_struct::TextualObject;
+    use crate::to_card::to_card_struct::ToCard;
+    use crate::to_parser::parser_option::ToParserOption;
+    use crate::to_ticket::to_ticket_spec::ToTicketSpec;
+    use crate::to_ticket::to_ticket_struct::ToTicket;
+
+    fn discipline_and_punish() -> TextualObject {
+        TextualObject {
+            ticket_id: "abc12".to_string(),
+            json: sqlx::types::Json(json!({"title": "Discipline and Punish", "author": "Michel Foucault", "date": "1975-02-01"})),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_display_label() {
+        let to = discipline_and_punish();
+        assert_eq!(to.display_label().unwrap(), "Foucault 1975, Discipline and Punish");
+        assert_eq!(to.print_labelled_ticket(), "[[id: abc12 | label: Foucault 1975, Discipline and Punish]]");
+        assert_eq!(to.print_minimal_ticket(), "[[id: abc12]]");
+        assert_eq!(TextualObject::default().display_label(), None);
+
+        // the card comes first
+        let mut to = discipline_and_punish();
+        let mut card = to.card.0.to_json();
+        card["title"] = json!("Surveiller et punir");
+        card["creators"] = json!({
+            "0": {"id": uuid::Uuid::new_v4(), "name": "Foucault, Michel", "description": "", "extra": []},
+            "1": {"id": uuid::Uuid::new_v4(), "name": "Alan Sheridan", "description": "", "extra": []},
+        });
+        to.card = sqlx::types::Json(serde_json::from_value::<ToCard>(card).unwrap());
+        assert_eq!(to.display_label().unwrap(), "Foucault & Sheridan 1975, Surveiller et punir");
+    }
+
+    #[test]
+    fn test_label_is_not_authoritative() {
+        let to = discipline_and_punish();
+        let opt = ToParserOption {
+            ticket_spec: ToTicketSpec::with_label(),
+            ..Default::default()
+        };
+        let (mut ticket, _) = ToTicket::parse("[[id: abc12 | label: Foucault 1974, Old title]]", &opt, None);
+        assert_eq!(ticket.to_label.as_deref(), Some("Foucault 1974, Old title"));
+        assert!(ticket.values.is_empty());
+        // the label does not make tickets different
+        let (mut unlabelled, _) = ToTicket::parse("[[id: abc12]]", &opt, None);
+        unlabelled.to_updated = ticket.to_updated;
+        assert_eq!(ticket, unlabelled);
+
+        assert!(ticket.refresh_label(&to));
+        assert!(!ticket.refresh_label(&to));
+        assert_eq!(ticket.print_labelled(), to.print_labelled_ticket());
+
+        // without requesting labels, label is a user value
+        let (ticket, _) = ToTicket::parse("[[id: abc12 | label: Foucault 1974, Old title]]", &ToParserOption::default(), None);
+        assert_eq!(ticket.to_label, None);
+        assert_eq!(ticket.values.get("label").unwrap(), "Foucault 1974, Old title");
+    }
+}
@@ -85,4 +85,12 @@ impl ToCard {
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap()
     }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn creators(&self) -> &IndexMap<usize, Person> {
+        &self.creators
+    }
 }
\ No newline at end of file
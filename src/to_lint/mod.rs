@@ -0,0 +1,3 @@
+pub mod to_lint_rule;
+pub mod to_lint_check;
+pub mod to_lint_report;
@@ -0,0 +1,355 @@
+use std::ops::Range;
+
+use crate::to::to_dtos::to_lint_dto::{ToLintFinding, ToLintFix};
+use crate::to_lint::to_lint_rule::ToLintRule;
+use crate::to_parser::markdown::ToMarkdownMask;
+use crate::to_parser::parser_diagnostic::{ToDiagnosticKind, ToParseDiagnostic, ToParseResult};
+use crate::to_parser::parser_option::ToParserOption;
+use crate::to_ticket::to_ticket_marker::{ToMarker, ToMarkerDialect};
+use crate::to_ticket::to_ticket_position::{line_starts, ToTicketPositionInfo};
+use crate::to_ticket::to_ticket_spec::ToTicketSpec;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+/// Findings of the rules that only need the text: the problems the parser found, unclosed markers,
+/// and the spacing and keys of each ticket. `result` is the scan of `text` with `opt`.
+/// Findings are in order of the text.
+pub fn lint_parse_result(text: &str, result: &ToParseResult, opt: &ToParserOption) -> Vec<ToLintFinding> {
+    let mut findings: Vec<ToLintFinding> = result.diagnostics.iter().filter_map(|diagnostic| diagnostic_finding(diagnostic, opt)).collect();
+    for ticket in result.tickets.iter() {
+        lint_ticket(ticket, opt, &mut findings);
+    }
+    findings.extend(unclosed_markers(text, result, opt));
+    findings.sort_by_key(|finding| (finding.byte_offset, finding.rule.code()));
+    findings
+}
+
+// finding of a problem found by the parser
+fn diagnostic_finding(diagnostic: &ToParseDiagnostic, opt: &ToParserOption) -> Option<ToLintFinding> {
+    let position = diagnostic.position.as_ref()?;
+    let (rule, fix) = match diagnostic.kind {
+        // no rule checks problems of no known kind
+        ToDiagnosticKind::Other => return None,
+        ToDiagnosticKind::EmptyId => (ToLintRule::EmptyId, ToLintFix {
+            description: "Write the id of the TO, or remove the id to capture the ticket as a new TO".to_string(),
+            replacement: None,
+        }),
+        ToDiagnosticKind::MalformedDate => (ToLintRule::MalformedDate, ToLintFix {
+            description: format!("Write the date with the format \"{}\"", opt.date_format),
+            replacement: None,
+        }),
+        ToDiagnosticKind::UnopenedSpan => (ToLintRule::UnpairedSpan, ToLintFix {
+            description: "Remove the closing ticket, or open the span before it".to_string(),
+            replacement: Some(String::new()),
+        }),
+        ToDiagnosticKind::OverlappingSpan => (ToLintRule::UnpairedSpan, ToLintFix {
+            description: "Close the span before the end of the span it starts in".to_string(),
+            replacement: None,
+        }),
+        // the scanner reads no ticket from the marker
+        ToDiagnosticKind::TicketTooLong => (ToLintRule::UnclosedMarker, ToLintFix {
+            description: format!("Shorten the ticket to at most {} bytes", opt.max_ticket_length),
+            replacement: None,
+        }),
+    };
+    Some(ToLintFinding {
+        fix: Some(fix),
+        ..ToLintFinding::new(rule, diagnostic.message.clone(), position)
+    })
+}
+
+// spacing and keys of a ticket
+fn lint_ticket(ticket: &ToTicket, opt: &ToParserOption, findings: &mut Vec<ToLintFinding>) {
+    let position = match &ticket.to_intext_option {
+        Some(position) => position,
+        None => return,
+    };
+    let marker = match ticket.to_dialect.marker(&opt.to_marker) {
+        Some(marker) => marker,
+        // a markdown link has no keys
+        None => return,
+    };
+    let raw_text = position.raw_text.as_str();
+    let ticket_finding = |rule: ToLintRule, message: String, fix: ToLintFix| ToLintFinding {
+        ticket_id: Some(ticket.ticket_id.clone()),
+        fix: Some(fix),
+        ..ToLintFinding::new(rule, message, position)
+    };
+
+    // spaces inside the markers, and between keys and the value separator
+    let content = raw_text.strip_prefix(marker.left_marker.as_str()).unwrap_or(raw_text);
+    let content = content.strip_suffix(marker.right_marker.as_str()).unwrap_or(content);
+    let spaced: Vec<String> = marker.split_unescaped(content, &marker.value_entry_separator, usize::MAX).into_iter()
+        .map(|entry| match marker.split_unescaped(entry, &marker.value_separator, 2)[..] {
+            [key, value] => format!("{}{}{}", key.trim_end(), marker.value_separator, value),
+            _ => entry.to_string(),
+        })
+        .collect();
+    let spaced = spaced.join(&marker.value_entry_separator);
+    let spaced = trim_content(&spaced, &marker);
+    if spaced != content {
+        findings.push(ticket_finding(
+            ToLintRule::MarkerSpacing,
+            format!("Ticket is written \"{}\" rather than \"{}{}{}\"", raw_text, marker.left_marker, spaced, marker.right_marker),
+            ToLintFix {
+                description: "Remove the spaces inside the markers and before the value separator".to_string(),
+                replacement: Some(format!("{}{}{}", marker.left_marker, spaced, marker.right_marker)),
+            },
+        ));
+    }
+
+    let entries = ticket.written_entries(opt);
+    for (key, entry) in entries.iter() {
+        if opt.ticket_spec.field_of(key).is_some() || !is_reserved_looking(key, &opt.ticket_spec) {
+            continue;
+        }
+        let suggestion = suggested_key(key, &opt.ticket_spec);
+        let fix = match &suggestion {
+            Some(suggested_key) => {
+                let written_key = marker.split_unescaped(entry, &marker.value_separator, 2)[0].trim();
+                let new_entry = entry.replacen(written_key, &marker.escape_key(suggested_key), 1);
+                ToLintFix {
+                    description: format!("Write the reserved key \"{}\"", suggested_key),
+                    replacement: Some(replace_ranges(raw_text, vec![(range_in(raw_text, entry), new_entry)])),
+                }
+            }
+            None => ToLintFix {
+                description: format!("Use one of the reserved keys {}, or a key without the prefix \"{}\"", reserved_keys(&opt.ticket_spec), opt.ticket_spec.reserved_prefix),
+                replacement: None,
+            },
+        };
+        findings.push(ticket_finding(ToLintRule::UnknownReservedKey, format!("Key \"{}\" is read as a value, not as a reserved key", key), fix));
+    }
+
+    // a value key written more than once is a list, but a reserved key keeps only its last value
+    let mut duplicate_keys: Vec<&str> = Vec::new();
+    for (index, (key, _)) in entries.iter().enumerate() {
+        if opt.ticket_spec.field_of(key).is_none() || duplicate_keys.contains(&key.as_str()) {
+            continue;
+        }
+        if entries[index + 1..].iter().any(|(later_key, _)| later_key == key) {
+            duplicate_keys.push(key);
+        }
+    }
+    for key in duplicate_keys {
+        let written: Vec<&str> = entries.iter().filter(|(written_key, _)| written_key == key).map(|(_, entry)| *entry).collect();
+        // the parser keeps the last value, the others are removed
+        let removed = written[..written.len() - 1].iter()
+            .map(|entry| (entry_with_separator(raw_text, entry, &marker), String::new()))
+            .collect();
+        findings.push(ticket_finding(
+            ToLintRule::DuplicateKey,
+            format!("Reserved key \"{}\" is written {} times, only the last value is kept", key, written.len()),
+            ToLintFix {
+                description: format!("Remove the earlier values of \"{}\"", key),
+                replacement: Some(replace_ranges(raw_text, removed)),
+            },
+        ));
+    }
+}
+
+// content of a ticket without the spaces inside its markers; an escaped space at the end is kept
+fn trim_content<'a>(content: &'a str, marker: &ToMarker) -> &'a str {
+    let content = content.trim_start();
+    let trimmed = content.trim_end();
+    if marker.ends_with_escape(trimmed) {
+        &content[..trimmed.len() + content[trimmed.len()..].chars().next().map_or(0, char::len_utf8)]
+    } else {
+        trimmed
+    }
+}
+
+// whether a key is meant as a reserved key: it has the reserved prefix, or is a reserved key written differently
+fn is_reserved_looking(key: &str, spec: &ToTicketSpec) -> bool {
+    (!spec.reserved_prefix.is_empty() && key.starts_with(&spec.reserved_prefix)) || suggested_key(key, spec).is_some()
+}
+
+// reserved key of the spec a key was meant to be, e.g. `updated` for `Updated` and `store_id` for `store_url`.
+// With a reserved prefix, keys without it are user values, except for the id which is never prefixed
+fn suggested_key(key: &str, spec: &ToTicketSpec) -> Option<String> {
+    let prefix = spec.reserved_prefix.as_str();
+    let name = key.strip_prefix(prefix).unwrap_or(key).to_lowercase();
+    let name = if name == "store_url" { "store_id".to_string() } else { name };
+    if !prefix.is_empty() && !key.starts_with(prefix) && name != "id" {
+        return None;
+    }
+    spec.fields().into_iter()
+        .map(|field| spec.key(field))
+        .find(|reserved| reserved != key && reserved.strip_prefix(prefix).unwrap_or(reserved) == name)
+}
+
+fn reserved_keys(spec: &ToTicketSpec) -> String {
+    let keys: Vec<String> = spec.fields().into_iter().map(|field| format!("\"{}\"", spec.key(field))).collect();
+    keys.join(", ")
+}
+
+// byte range of a slice of the text within it
+fn range_in(text: &str, slice: &str) -> Range<usize> {
+    let start = slice.as_ptr() as usize - text.as_ptr() as usize;
+    start..start + slice.len()
+}
+
+// range of an entry with a separator next to it, so that the ticket reads the same once it is removed:
+// the separator before it, or the separator after it and the spaces that follow for a first entry
+fn entry_with_separator(raw_text: &str, entry: &str, marker: &ToMarker) -> Range<usize> {
+    let range = range_in(raw_text, entry);
+    let separator = marker.value_entry_separator.as_str();
+    if raw_text[marker.left_marker.len()..range.start].ends_with(separator) {
+        return range.start - separator.len()..range.end;
+    }
+    let after = &raw_text[range.end..];
+    let after = after.strip_prefix(separator).map_or(after, str::trim_start);
+    range.start..raw_text.len() - after.len()
+}
+
+// the text with the ranges replaced; the ranges do not overlap
+fn replace_ranges(text: &str, mut replacements: Vec<(Range<usize>, String)>) -> String {
+    replacements.sort_by_key(|(range, _)| range.start);
+    let mut replaced = String::with_capacity(text.len());
+    let mut copied_until = 0;
+    for (range, replacement) in replacements {
+        replaced.push_str(&text[copied_until..range.start]);
+        replaced.push_str(&replacement);
+        copied_until = range.end;
+    }
+    replaced.push_str(&text[copied_until..]);
+    replaced
+}
+
+// left markers that no right marker closes before the next left marker, within the lines a ticket may span,
+// e.g. `[[id:abc` alone, or `[[id:abc and [[id:def]]` which the parser reads as one ticket
+fn unclosed_markers(text: &str, result: &ToParseResult, opt: &ToParserOption) -> Vec<ToLintFinding> {
+    let read: Vec<Range<usize>> = result.tickets.iter()
+        .filter_map(|ticket| ticket.to_intext_option.as_ref())
+        .chain(result.spans.iter().map(|span| &span.close))
+        .chain(result.diagnostics.iter().filter_map(|diagnostic| diagnostic.position.as_ref()))
+        .map(|position| position.byte_offset..position.end_byte_offset)
+        .collect();
+    let excluded = if opt.markdown { ToMarkdownMask::excluded_ranges(text) } else { Vec::new() };
+    let mut dialects: Vec<ToMarkerDialect> = Vec::new();
+    for dialect in opt.dialects.iter() {
+        if !dialects.contains(dialect) {
+            dialects.push(*dialect);
+        }
+    }
+    if dialects.is_empty() {
+        dialects.push(ToMarkerDialect::default());
+    }
+
+    let line_starts = line_starts(text);
+    let mut findings = Vec::new();
+    for marker in dialects.iter().filter_map(|dialect| dialect.marker(&opt.to_marker)) {
+        let (left, right) = (marker.left_marker.as_str(), marker.right_marker.as_str());
+        for (start, _) in text.match_indices(left) {
+            // markers inside a ticket are reported with the ticket they are in
+            let inside_ticket = read.iter().any(|range| range.start < start && start < range.end);
+            if marker.ends_with_escape(&text[..start]) || inside_ticket || excluded.iter().any(|range| range.contains(&start)) {
+                continue;
+            }
+            let rest = &text[start + left.len()..];
+            let closed = match (find_unescaped(rest, right, &marker), find_unescaped(rest, left, &marker)) {
+                (Some(end), next_left) => end < next_left.unwrap_or(usize::MAX)
+                    && rest[..end].matches('\n').count() < opt.max_lines_per_ticket.max(1),
+                (None, _) => false,
+            };
+            if closed {
+                continue;
+            }
+            let position = ToTicketPositionInfo::from_span(text, start, start + left.len(), &line_starts);
+            findings.push(ToLintFinding {
+                fix: Some(ToLintFix {
+                    description: format!("Close the ticket with \"{}\", or write the marker as \"{}\" if it is not a ticket", right, marker.escape_value(left)),
+                    replacement: None,
+                }),
+                ..ToLintFinding::new(ToLintRule::UnclosedMarker, format!("\"{}\" is not closed by \"{}\"", left, right), &position)
+            });
+        }
+    }
+    findings
+}
+
+// offset of the first occurrence of the pattern that is not escaped
+fn find_unescaped(text: &str, pattern: &str, marker: &ToMarker) -> Option<usize> {
+    text.match_indices(pattern).map(|(index, _)| index).find(|index| !marker.ends_with_escape(&text[..*index]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::to_parser::parser::ToParser;
+
+    use super::*;
+
+    fn lint(text: &str, opt: ToParserOption) -> Vec<ToLintFinding> {
+        let result = ToParser::scan_text_for_tickets(text, opt.clone());
+        lint_parse_result(text, &result, &opt)
+    }
+
+    fn codes(findings: &[ToLintFinding]) -> Vec<&str> {
+        findings.iter().map(|finding| finding.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_clean_text() {
+        let text = "[[id:1]] [[id: 2 | title: A \\[\\[ | p: 3]]\n[[id:a|label:Note]]b[[/id:a]] [[IMPORTANT]]";
+        assert!(lint(text, ToParserOption::default()).is_empty());
+    }
+
+    #[test]
+    fn test_malformed_tickets() {
+        let text = "A [[ id : abc ]] and [[id:|p:1]], [[id:abc|updated:yesterday]]\n[[id:open and more\n[[id:2]] \\[[ escaped";
+        let findings = lint(text, ToParserOption::default());
+        assert_eq!(codes(&findings), vec!["TO003", "TO002", "TO004", "TO001"]);
+
+        let spacing = &findings[0];
+        assert_eq!(spacing.ticket_id.as_deref(), Some("abc"));
+        assert_eq!((spacing.line, spacing.char_column, spacing.raw_text.as_str()), (0, 2, "[[ id : abc ]]"));
+        assert_eq!(spacing.fix.as_ref().unwrap().replacement.as_deref(), Some("[[id: abc]]"));
+
+        let unclosed = &findings[3];
+        assert_eq!((unclosed.line, unclosed.char_column, unclosed.end_char_column), (1, 0, 2));
+        assert_eq!(unclosed.message, "\"[[\" is not closed by \"]]\"");
+        assert!(lint("[[id:open", ToParserOption::default())[0].fix.is_some());
+
+        // a closing ticket without opening ticket can be removed
+        let findings = lint("[[id:a]] [[/id:b]]", ToParserOption::default());
+        assert_eq!(codes(&findings), vec!["TO007"]);
+        assert_eq!(findings[0].fix.as_ref().unwrap().replacement.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn test_keys() {
+        let text = "[[id:abc | Updated: 2020-01-01 00:00:00 | store_url: x]] [[updated: 2020-01-01 00:00:00 | id:abc | t: 1 | updated: 2021-01-01 00:00:00]]";
+        let findings = lint(text, ToParserOption::default());
+        assert_eq!(codes(&findings), vec!["TO005", "TO005", "TO006"]);
+        assert_eq!(findings[0].message, "Key \"Updated\" is read as a value, not as a reserved key");
+        assert_eq!(findings[0].fix.as_ref().unwrap().replacement.as_deref(), Some("[[id:abc | updated: 2020-01-01 00:00:00 | store_url: x]]"));
+        assert_eq!(findings[1].fix.as_ref().unwrap().replacement.as_deref(), Some("[[id:abc | Updated: 2020-01-01 00:00:00 | store_id: x]]"));
+        assert_eq!(findings[2].message, "Reserved key \"updated\" is written 2 times, only the last value is kept");
+        assert_eq!(findings[2].fix.as_ref().unwrap().replacement.as_deref(), Some("[[id:abc | t: 1 | updated: 2021-01-01 00:00:00]]"));
+
+        // a value key written more than once is a list, which is printed the same way
+        assert!(lint("[[id:abc | p: 1 | p: 2 | p: 3]]", ToParserOption::default()).is_empty());
+
+        // with a prefix, keys with the prefix are reserved and the others are values
+        let opt = ToParserOption {
+            ticket_spec: ToTicketSpec::with_prefix("@"),
+            ..Default::default()
+        };
+        let findings = lint("[[id:abc | updated: soon | @update: 2020-01-01 00:00:00 | ID: x]]", opt);
+        assert_eq!(codes(&findings), vec!["TO005", "TO005"]);
+        assert!(findings[0].message.contains("@update"));
+        assert!(findings[0].fix.as_ref().unwrap().replacement.is_none());
+        assert_eq!(findings[1].fix.as_ref().unwrap().replacement.as_deref(), Some("[[id:abc | updated: soon | @update: 2020-01-01 00:00:00 | id: x]]"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let opt = ToParserOption {
+            markdown: true,
+            ..Default::default()
+        };
+        let text = "See [[Some note]] and `[[` in code\n```\n[[ id : 1 ]]\n```\n[[ id : 2 ]]";
+        let findings = lint(text, opt);
+        assert_eq!(codes(&findings), vec!["TO003"]);
+        assert_eq!(findings[0].ticket_id.as_deref(), Some("2"));
+    }
+}
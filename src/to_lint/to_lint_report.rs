@@ -0,0 +1,161 @@
+use serde_json::{json, Value};
+
+use crate::to::to_dtos::to_lint_dto::{ToLintFinding, ToLintReportDto};
+use crate::to_lint::to_lint_rule::ToLintRule;
+
+impl ToLintReportDto {
+    /// the report as pretty printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// The report as a SARIF 2.1.0 log, as read by code scanning tools and editors.
+    /// Lines and columns count from 1 in SARIF, and columns are in code points;
+    /// fixes without a replacement are given in the properties of their result.
+    pub fn to_sarif(&self) -> Value {
+        let rules: Vec<Value> = ToLintRule::all().iter().map(|rule| json!({
+            "id": rule.code(),
+            "name": rule.name(),
+            "shortDescription": {"text": rule.description()},
+            "defaultConfiguration": {"level": rule.severity().sarif_level()},
+        })).collect();
+        let results: Vec<Value> = self.findings.iter().map(|finding| self.sarif_result(finding)).collect();
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "columnKind": "unicodeCodePoints",
+                "results": results,
+            }]
+        })
+    }
+
+    fn sarif_result(&self, finding: &ToLintFinding) -> Value {
+        let region = json!({
+            "startLine": finding.line + 1,
+            "startColumn": finding.char_column + 1,
+            "endLine": finding.end_line + 1,
+            "endColumn": finding.end_char_column + 1,
+            "snippet": {"text": finding.raw_text},
+        });
+        let mut result = json!({
+            "ruleId": finding.code,
+            "ruleIndex": ToLintRule::all().iter().position(|rule| *rule == finding.rule),
+            "level": finding.severity.sarif_level(),
+            "message": {"text": finding.message},
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": {"uri": self.source},
+                    "region": region,
+                }
+            }],
+        });
+        if let Some(ticket_id) = &finding.ticket_id {
+            result["properties"]["ticketId"] = json!(ticket_id);
+        }
+        if let Some(fix) = &finding.fix {
+            match &fix.replacement {
+                Some(replacement) => {
+                    result["fixes"] = json!([{
+                        "description": {"text": fix.description},
+                        "artifactChanges": [{
+                            "artifactLocation": {"uri": self.source},
+                            "replacements": [{
+                                "deletedRegion": region,
+                                "insertedContent": {"text": replacement},
+                            }],
+                        }],
+                    }]);
+                }
+                None => result["properties"]["fix"] = json!(fix.description),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::to::to_dtos::to_lint_dto::ToLintFix;
+    use crate::to_ticket::to_ticket_position::{line_starts, ToTicketPositionInfo};
+
+    use super::*;
+
+    fn report() -> ToLintReportDto {
+        let text = "Intro\nsee [[ id : é ]] and [[id:x";
+        let line_starts = line_starts(text);
+        let spacing = text.find("[[ id").unwrap();
+        let unclosed = text.find("[[id:x").unwrap();
+        ToLintReportDto {
+            store_url: "store.db".to_string(),
+            source: "notes/chapter.md".to_string(),
+            findings: vec![
+                ToLintFinding {
+                    ticket_id: Some("é".to_string()),
+                    fix: Some(ToLintFix {
+                        description: "Remove the spaces".to_string(),
+                        replacement: Some("[[id: é]]".to_string()),
+                    }),
+                    ..ToLintFinding::new(ToLintRule::MarkerSpacing, "Spacing".to_string(), &ToTicketPositionInfo::from_span(text, spacing, spacing + "[[ id : é ]]".len(), &line_starts))
+                },
+                ToLintFinding {
+                    fix: Some(ToLintFix {
+                        description: "Close the ticket".to_string(),
+                        replacement: None,
+                    }),
+                    ..ToLintFinding::new(ToLintRule::UnclosedMarker, "Unclosed".to_string(), &ToTicketPositionInfo::from_span(text, unclosed, unclosed + 2, &line_starts))
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let report = report();
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["findings"][0]["code"], "TO003");
+        assert_eq!(json["findings"][0]["rule"], "marker_spacing");
+        assert_eq!(json["findings"][0]["severity"], "warning");
+        assert_eq!(json["findings"][1]["fix"]["replacement"], Value::Null);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn test_sarif() {
+        let sarif = report().to_sarif();
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), ToLintRule::all().len());
+        assert_eq!(run["tool"]["driver"]["rules"][2]["id"], "TO003");
+
+        let spacing = &run["results"][0];
+        assert_eq!(spacing["ruleId"], "TO003");
+        assert_eq!(spacing["ruleIndex"], 2);
+        assert_eq!(spacing["level"], "warning");
+        assert_eq!(spacing["properties"]["ticketId"], "é");
+        let location = &spacing["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "notes/chapter.md");
+        assert_eq!(location["region"], json!({
+            "startLine": 2,
+            "startColumn": 5,
+            "endLine": 2,
+            "endColumn": 17,
+            "snippet": {"text": "[[ id : é ]]"},
+        }));
+        let replacement = &spacing["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], "[[id: é]]");
+        assert_eq!(replacement["deletedRegion"]["startColumn"], 5);
+
+        let unclosed = &run["results"][1];
+        assert_eq!(unclosed["level"], "error");
+        assert!(unclosed.get("fixes").is_none());
+        assert_eq!(unclosed["properties"]["fix"], "Close the ticket");
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How serious a finding is, named after the levels of SARIF
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToLintSeverity {
+    // the ticket does not refer to what the writer meant
+    Error,
+    // the ticket is read, but probably not as the writer meant
+    Warning,
+    // worth knowing, nothing is wrong with the ticket
+    Note,
+}
+
+impl ToLintSeverity {
+    /// the level of a SARIF result
+    pub fn sarif_level(&self) -> &'static str {
+        match self {
+            ToLintSeverity::Error => "error",
+            ToLintSeverity::Warning => "warning",
+            ToLintSeverity::Note => "note",
+        }
+    }
+}
+
+/// Rules of the ticket linter.
+/// Codes are stable: a rule keeps its code once released, and a new rule gets the next free code.
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToLintRule {
+    // a left marker no right marker closes, e.g. `[[id:abc` alone
+    UnclosedMarker,
+    // `[[id:]]`
+    EmptyId,
+    // spaces inside the markers or before the value separator, e.g. `[[ id : abc ]]`
+    MarkerSpacing,
+    // a date that cannot be read with the date format
+    MalformedDate,
+    // a key written like a reserved key that is not one, e.g. `Updated` or `store_url`
+    UnknownReservedKey,
+    // a reserved key written twice in one ticket, only the last value is kept; value keys written twice are lists
+    DuplicateKey,
+    // a closing ticket without opening ticket, or a span overlapping another
+    UnpairedSpan,
+    // a ticket with the store_id of another store
+    OtherStore,
+    // a ticket whose id is not in the store
+    MissingTo,
+}
+
+impl ToLintRule {
+    pub fn all() -> Vec<ToLintRule> {
+        vec![
+            ToLintRule::UnclosedMarker,
+            ToLintRule::EmptyId,
+            ToLintRule::MarkerSpacing,
+            ToLintRule::MalformedDate,
+            ToLintRule::UnknownReservedKey,
+            ToLintRule::DuplicateKey,
+            ToLintRule::UnpairedSpan,
+            ToLintRule::OtherStore,
+            ToLintRule::MissingTo,
+        ]
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ToLintRule::UnclosedMarker => "TO001",
+            ToLintRule::EmptyId => "TO002",
+            ToLintRule::MarkerSpacing => "TO003",
+            ToLintRule::MalformedDate => "TO004",
+            ToLintRule::UnknownReservedKey => "TO005",
+            ToLintRule::DuplicateKey => "TO006",
+            ToLintRule::UnpairedSpan => "TO007",
+            ToLintRule::OtherStore => "TO008",
+            ToLintRule::MissingTo => "TO009",
+        }
+    }
+
+    /// name of the rule, as it is serialized
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToLintRule::UnclosedMarker => "unclosed_marker",
+            ToLintRule::EmptyId => "empty_id",
+            ToLintRule::MarkerSpacing => "marker_spacing",
+            ToLintRule::MalformedDate => "malformed_date",
+            ToLintRule::UnknownReservedKey => "unknown_reserved_key",
+            ToLintRule::DuplicateKey => "duplicate_key",
+            ToLintRule::UnpairedSpan => "unpaired_span",
+            ToLintRule::OtherStore => "other_store",
+            ToLintRule::MissingTo => "missing_to",
+        }
+    }
+
+    pub fn severity(&self) -> ToLintSeverity {
+        match self {
+            ToLintRule::UnclosedMarker
+            | ToLintRule::EmptyId
+            | ToLintRule::MalformedDate
+            | ToLintRule::UnpairedSpan
+            | ToLintRule::MissingTo => ToLintSeverity::Error,
+            ToLintRule::MarkerSpacing | ToLintRule::UnknownReservedKey | ToLintRule::DuplicateKey => ToLintSeverity::Warning,
+            ToLintRule::OtherStore => ToLintSeverity::Note,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ToLintRule::UnclosedMarker => "Ticket marker is not closed",
+            ToLintRule::EmptyId => "Ticket has an empty id",
+            ToLintRule::MarkerSpacing => "Ticket is not spaced as tickets are printed",
+            ToLintRule::MalformedDate => "Date cannot be read with the date format",
+            ToLintRule::UnknownReservedKey => "Key looks like a reserved key but is read as a value",
+            ToLintRule::DuplicateKey => "Reserved key is written more than once in a ticket",
+            ToLintRule::UnpairedSpan => "Closing ticket does not pair with an opening ticket",
+            ToLintRule::OtherStore => "Ticket refers to another store",
+            ToLintRule::MissingTo => "No TO with the id of the ticket in the store",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // codes are part of the reports users keep, so they must not change
+    #[test]
+    fn test_codes_are_stable() {
+        let codes: Vec<&str> = ToLintRule::all().iter().map(|rule| rule.code()).collect();
+        assert_eq!(codes, vec!["TO001", "TO002", "TO003", "TO004", "TO005", "TO006", "TO007", "TO008", "TO009"]);
+        for rule in ToLintRule::all() {
+            assert_eq!(serde_json::to_value(rule).unwrap(), serde_json::json!(rule.name()));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::db::db_op::reset_database;
+use crate::to_machine::to_machine_event::ToEvent;
 use crate::to_machine::to_machine_struct::ToMachine;
 
 pub mod to_machine_struct;
@@ -6,12 +7,14 @@ pub mod to_machine_option;
 pub mod to_machine_op;
 pub mod to_machine_db;
 pub mod to_machine_pub_op;
+pub mod to_machine_event;
 
 // implement db related methods for TextualObjectMachine
 impl ToMachine {
     // clear all tables;
     pub async fn reset_db(&self) -> () {
         reset_database(self.store_url.as_ref()).await;
+        self.publish(ToEvent::Reset { store_url: self.store_url.clone() });
     }
 }
 
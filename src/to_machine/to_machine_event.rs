@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::Receiver;
+
+use crate::to::to_struct::TextualObject;
+use crate::to_machine::to_machine_struct::ToMachine;
+
+/// Events published by ToMachine whenever data in the store changes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ToEvent {
+    Added { ticket_id: String, to: TextualObject },
+    Updated { ticket_id: String, to: TextualObject },
+    Deleted { ticket_id: String, to: TextualObject },
+    // all tos in the store have been removed
+    Reset { store_url: String },
+}
+
+impl ToEvent {
+    // ticket id of the affected TO, None for store-wide events
+    pub fn ticket_id(&self) -> Option<&str> {
+        match self {
+            ToEvent::Added { ticket_id, .. } => Some(ticket_id),
+            ToEvent::Updated { ticket_id, .. } => Some(ticket_id),
+            ToEvent::Deleted { ticket_id, .. } => Some(ticket_id),
+            ToEvent::Reset { .. } => None,
+        }
+    }
+}
+
+impl ToMachine {
+    /// subscribe to change events of the store.
+    /// Receivers only get events published after they subscribed; slow receivers that fall behind the channel capacity get a `Lagged` error and miss the oldest events.
+    pub fn subscribe(&self) -> Receiver<ToEvent> {
+        self.event_sender.subscribe()
+    }
+
+    pub(crate) fn publish(&self, event: ToEvent) {
+        // sending only fails when there is no subscriber, which is fine
+        let _ = self.event_sender.send(event);
+    }
+}
+
+// test
+#[cfg(test)]
+mod test {
+    use crate::enums::store_type::StoreType;
+    use crate::to::to_struct::TextualObject;
+    use crate::to_machine::to_machine_event::ToEvent;
+    use crate::to_machine::to_machine_option::ToMachineOption;
+    use crate::to_machine::to_machine_struct::ToMachine;
+    use crate::utils::get_random_test_database_dir::get_random_test_database_dir;
+
+    #[tokio::test]
+    async fn test_events_for_add_and_delete() {
+        let mut tom = ToMachine::new(&get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+            use_random_file_name: true,
+            ..Default::default()
+        })).await;
+        let mut receiver = tom.subscribe();
+
+        let sample_to = TextualObject::get_sample();
+        tom.add_textual_object(&sample_to).await;
+        match receiver.recv().await.unwrap() {
+            ToEvent::Added { ticket_id, to } => {
+                assert_eq!(ticket_id, sample_to.ticket_id);
+                assert_eq!(to.id, sample_to.id);
+            }
+            e => panic!("Expected added event, got {:?}", e),
+        }
+
+        tom.delete(&sample_to.ticket_id).await;
+        match receiver.recv().await.unwrap() {
+            ToEvent::Deleted { ticket_id, to } => {
+                assert_eq!(ticket_id, sample_to.ticket_id);
+                assert_eq!(to.id, sample_to.id);
+            }
+            e => panic!("Expected deleted event, got {:?}", e),
+        }
+
+        // deleting a missing ticket publishes nothing
+        tom.delete(&sample_to.ticket_id).await;
+        tom.reset_db().await;
+        let reset_event = receiver.recv().await.unwrap();
+        assert!(matches!(reset_event, ToEvent::Reset { .. }));
+        assert_eq!(reset_event.ticket_id(), None);
+    }
+}
@@ -2,10 +2,15 @@
 
 use std::borrow::BorrowMut;
 
+use chrono::Utc;
+use serde_json::json;
 use uuid::Uuid;
 
-use crate::db::to_db_op::{check_if_ticket_id_exists, count_textual_objects, delete_to_by_ticket_id, find_to_by_ticket_id, insert_to};
+use crate::db::to_db_op::{check_if_ticket_id_exists, count_textual_objects, delete_to_by_ticket_id, delete_to_by_ticket_id_if_version_matches, find_to_by_ticket_id, insert_to, update_to_if_version_matches};
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::error::error_message::ToErrorMessage;
 use crate::to::to_struct::TextualObject;
+use crate::to_machine::to_machine_event::ToEvent;
 use crate::to_machine::to_machine_struct::ToMachine;
 use crate::utils::id_generator::generate_id;
 
@@ -25,6 +30,7 @@ impl ToMachine {
         let id = insert_to(pool.borrow_mut(), textual_object).await;
         // update to_count
         self.update_to_count().await;
+        self.publish(ToEvent::Added { ticket_id: textual_object.ticket_id.clone(), to: textual_object.clone() });
         id
     }
 
@@ -54,17 +60,72 @@ impl ToMachine {
 
     // delete by ticket id, return true if successful
     pub async fn delete(&mut self, ticket_id: &String) -> bool {
+        // keep the TO to tell subscribers what has been deleted
+        let to_delete = self.find(ticket_id).await;
         let mut pool = self.get_pool().await;
         let result = delete_to_by_ticket_id(pool.borrow_mut(), ticket_id).await;
         // update to_count
         self.update_to_count().await;
         if result.rows_affected() == 1 {
+            if let Some(to) = to_delete {
+                self.publish(ToEvent::Deleted { ticket_id: ticket_id.clone(), to });
+            }
             true
         } else {
             false
         }
     }
 
+    /// update the stored TO with the same ticket id, only if it is still at `expected_version`.
+    /// Returns the stored TO with its new version, or a `ConflictError` carrying the current stored TO so that the client can merge and retry.
+    pub async fn update(&mut self, textual_object: &TextualObject, expected_version: i64) -> Result<TextualObject, ToErrors> {
+        let mut to_update = textual_object.clone();
+        to_update.updated = Utc::now().naive_utc();
+        let mut pool = self.get_pool().await;
+        let result = update_to_if_version_matches(pool.borrow_mut(), &to_update, expected_version).await;
+        if result.rows_affected() == 0 {
+            return Err(self.version_mismatch_error(&textual_object.ticket_id, expected_version).await);
+        }
+        to_update.version = expected_version + 1;
+        self.publish(ToEvent::Updated { ticket_id: to_update.ticket_id.clone(), to: to_update.clone() });
+        Ok(to_update)
+    }
+
+    /// delete by ticket id, only if the stored TO is still at `expected_version`
+    pub async fn delete_with_version(&mut self, ticket_id: &str, expected_version: i64) -> Result<TextualObject, ToErrors> {
+        let mut pool = self.get_pool().await;
+        let to_delete = find_to_by_ticket_id(pool.borrow_mut(), ticket_id).await;
+        let result = delete_to_by_ticket_id_if_version_matches(pool.borrow_mut(), ticket_id, expected_version).await;
+        match to_delete {
+            Some(to) if result.rows_affected() == 1 => {
+                self.update_to_count().await;
+                self.publish(ToEvent::Deleted { ticket_id: ticket_id.to_string(), to: to.clone() });
+                Ok(to)
+            }
+            _ => Err(self.version_mismatch_error(ticket_id, expected_version).await),
+        }
+    }
+
+    // tell apart a missing TO from a TO whose version has moved on
+    async fn version_mismatch_error(&mut self, ticket_id: &str, expected_version: i64) -> ToErrors {
+        let mut error_message = TextualObjectErrorMessage {
+            payload_from_user: json!({ "ticket_id": ticket_id, "expected_version": expected_version }),
+            ..Default::default()
+        };
+        match self.find(ticket_id).await {
+            None => {
+                error_message.message = ToErrorMessage::TicketIdNotFound.to_string();
+                ToErrors::NotFoundError(error_message)
+            }
+            Some(current) => {
+                error_message.message = ToErrorMessage::VersionConflict.to_string();
+                error_message.suggestion = format!("Merge your changes with the stored version {} and retry.", current.version);
+                error_message.payload_for_user = json!(current);
+                ToErrors::ConflictError(error_message)
+            }
+        }
+    }
+
     pub async fn get_unique_ticket_id(&mut self) -> String {
         let mut unique_ticket_id_to_try = generate_id();
         let mut pool = self.get_pool().await;
@@ -84,6 +145,8 @@ mod test {
     use std::path::PathBuf;
 
     use crate::enums::store_type::StoreType;
+    use crate::error::error_message::ToErrorMessage;
+    use crate::error::ToErrors;
     use crate::to::to_struct::TextualObject;
     use crate::to_machine::to_machine_option::ToMachineOption;
     use crate::to_machine::to_machine_struct::ToMachine;
@@ -218,6 +281,47 @@ mod test {
         assert_eq!(&found_tos[0].ticket_id, &sample_to1.ticket_id);
     }
 
+    // test update with expected version
+    #[tokio::test]
+    async fn test_update_with_version() {
+        let mut tom = ToMachine::new(&get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+            use_random_file_name: true,
+            ..Default::default()
+        })).await;
+        let mut sample_to = TextualObject::get_sample();
+        tom.add_textual_object(&sample_to).await;
+        assert_eq!(tom.find(&sample_to.ticket_id).await.unwrap().version, 1);
+
+        // first writer succeeds
+        sample_to.source_name = "first".to_string();
+        let updated = tom.update(&sample_to, 1).await.unwrap();
+        assert_eq!(updated.version, 2);
+        let stored = tom.find(&sample_to.ticket_id).await.unwrap();
+        assert_eq!(stored.version, 2);
+        assert_eq!(stored.source_name, "first");
+
+        // second writer still holds version 1 and gets a conflict with the stored TO
+        sample_to.source_name = "second".to_string();
+        match tom.update(&sample_to, 1).await {
+            Err(ToErrors::ConflictError(e)) => {
+                assert_eq!(e.message, ToErrorMessage::VersionConflict.to_string());
+                let current: TextualObject = serde_json::from_value(e.payload_for_user).unwrap();
+                assert_eq!(current.version, 2);
+                assert_eq!(current.source_name, "first");
+            }
+            _ => panic!("Expected conflict error"),
+        }
+        assert_eq!(tom.find(&sample_to.ticket_id).await.unwrap().source_name, "first");
+
+        // stale delete is refused, current delete succeeds
+        assert!(tom.delete_with_version(&sample_to.ticket_id, 1).await.is_err());
+        assert!(tom.delete_with_version(&sample_to.ticket_id, 2).await.is_ok());
+        match tom.update(&sample_to, 2).await {
+            Err(ToErrors::NotFoundError(_)) => {}
+            _ => panic!("Expected not found error"),
+        }
+    }
+
     // test get unique ticket id
     #[tokio::test]
     async fn test_get_unique_ticket_id() {
@@ -12,6 +12,9 @@ pub struct ToMachineOption {
 
     // store type
     pub store_type: StoreType,
+
+    // number of change events kept for subscribers that have not received them yet
+    pub event_capacity: usize,
 }
 
 // impl default for ToMachineOption
@@ -21,7 +24,8 @@ impl Default for ToMachineOption {
             use_random_file_name: false,
             store_file_name: None,
             store_info: Some("A TO Store".to_string()),
-            store_type: StoreType::SQLITE
+            store_type: StoreType::SQLITE,
+            event_capacity: 64,
         }
     }
 }
@@ -1,14 +1,32 @@
+use std::borrow::BorrowMut;
 use std::collections::HashMap;
+use indexmap::IndexMap;
+use crate::db::to_db_op::{average_json_size, count_cards_with_creators, count_cards_with_title, count_textual_objects_by_day, count_textual_objects_group_by, delete_to_by_ticket_id, find_to_by_ticket_id, find_tos_by_source};
 use crate::error::ToErrors;
 
 use crate::to::to_dtos::to_add_dto::{ToAddManyDto, TextualObjectStoredReceipt};
-use crate::to::to_dtos::to_find_dto::{ToFindRequestDto, ToFindResultDto};
-use crate::to::to_dtos::to_scan_dto::{ToScanRequestDto, ToScanResultDto};
+use crate::to::to_dtos::to_capture_dto::{ToCaptureRequestDto, ToCaptureResultDto};
+use crate::to::to_dtos::to_delete_dto::{TextualObjectDeletedReceipt, ToDeleteManyDto};
+use crate::to::to_dtos::to_find_dto::{ToFindRequestDto, ToFindResultDto, ToSourceIdentity, ToSourceIdentityMatch};
+use crate::to::to_dtos::to_lint_dto::{ToLintFinding, ToLintFix, ToLintReportDto, ToLintRequestDto};
+use crate::to::to_dtos::to_rewrite_dto::{ToRewriteRequestDto, ToRewriteResultDto, ToTicketRewrite};
+use crate::to::to_dtos::to_scan_dto::{ToCleanMode, ToScanRequestDto, ToScanResultDto, ToScanTicketOccurrences, ToStaleTicket, ToTicketOccurrence};
+use crate::to::to_dtos::to_stats_dto::ToStatsDto;
+use crate::to::to_rewrite::ToRewriteContext;
 use crate::to::to_struct::TextualObject;
+use crate::to_lint::to_lint_check::lint_parse_result;
+use crate::to_lint::to_lint_rule::ToLintRule;
+use crate::to_machine::to_machine_event::ToEvent;
 use crate::to_machine::to_machine_struct::ToMachine;
+use crate::to_parser::cleaner::{clean_text, clean_text_with_spans};
 use crate::to_parser::parser::ToParser;
 use crate::to_parser::parser_option::ToParserOption;
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_spec::ToTicketField;
 use crate::to_ticket::to_ticket_struct::ToTicket;
+use crate::to_ticket::to_ticket_utils::print_minimal_ticket;
+use crate::utils::split_store_path::split_store_path;
+use crate::utils::unified_diff::unified_diff;
 
 /// These are methods mostly exposed to the ToApi, such batch adding dtos etc--why it's called public operation methods
 ///
@@ -58,7 +76,70 @@ impl ToMachine {
         Ok(receipt)
     }
 
-    /// find TOs by ticket ids
+    /// delete from ToDeleteManyDto, main method for deleting many TOs at once
+    pub async fn delete_tos(&mut self, delete_dto: &ToDeleteManyDto) -> Result<TextualObjectDeletedReceipt, ToErrors> {
+        // validate dto
+        let is_valid = delete_dto.validate();
+        match is_valid {
+            Ok(_) => {}
+            Err(e) => {
+                return Err(e);
+            }
+        }
+
+        let mut receipt = TextualObjectDeletedReceipt::new(&self.store_url);
+        let mut pool = self.get_pool().await;
+
+        // collect selected TOs by ticket id so that a TO selected twice is deleted once
+        let mut selected_tos: IndexMap<String, TextualObject> = IndexMap::new();
+        for ticket_id in delete_dto.ticket_ids.iter() {
+            match find_to_by_ticket_id(pool.borrow_mut(), ticket_id).await {
+                Some(to) => {
+                    selected_tos.insert(ticket_id.clone(), to);
+                }
+                None => {
+                    receipt.not_found_ticket_ids.push(ticket_id.clone());
+                }
+            }
+        }
+        let source_name = delete_dto.source_name.as_deref().filter(|name| !name.is_empty());
+        for source_id in delete_dto.source_ids.iter() {
+            let found_tos = find_tos_by_source(pool.borrow_mut(), source_name, None, Some(source_id)).await;
+            if found_tos.is_empty() {
+                receipt.not_found_source_ids.push(source_id.clone());
+            }
+            for to in found_tos {
+                selected_tos.insert(to.ticket_id.clone(), to);
+            }
+        }
+        // source name alone selects the whole source
+        if delete_dto.source_ids.is_empty() && source_name.is_some() {
+            for to in find_tos_by_source(pool.borrow_mut(), source_name, None, None).await {
+                selected_tos.insert(to.ticket_id.clone(), to);
+            }
+        }
+
+        for (ticket_id, to) in selected_tos {
+            if delete_dto.protected_ticket_ids.contains(&ticket_id) {
+                receipt.protected_ticket_ids.push(ticket_id);
+                continue;
+            }
+            let result = delete_to_by_ticket_id(pool.borrow_mut(), &ticket_id).await;
+            if result.rows_affected() > 0 {
+                receipt.deleted_ticket_ids.push(ticket_id.clone());
+                self.publish(ToEvent::Deleted { ticket_id, to });
+            } else {
+                receipt.not_found_ticket_ids.push(ticket_id);
+            }
+        }
+        receipt.total_tos_deleted = receipt.deleted_ticket_ids.len();
+
+        // update to_count once for the whole batch
+        self.update_to_count().await;
+        Ok(receipt)
+    }
+
+    /// find TOs by ticket ids and source identities
     pub async fn find_tos_by_ticket_ids(&mut self, find_request_dto: &ToFindRequestDto) -> Result<ToFindResultDto, ToErrors> {
         // validate dto
         let is_valid = find_request_dto.validate();
@@ -69,20 +150,54 @@ impl ToMachine {
             }
         }
         // find by ticket ids
-        let (found_tos, missing_to_ids) = self.find_by_ticket_ids(&find_request_dto.ticket_ids).await;
+        let (mut found_tos, missing_to_ids) = self.find_by_ticket_ids(&find_request_dto.ticket_ids).await;
+        // find by source identities, adding TOs not yet found by ticket id
+        let (tos_by_source, found_source_identities, missing_source_identities) = self.find_by_source_identities(&find_request_dto.source_identities).await;
+        for to in tos_by_source {
+            if !found_tos.iter().any(|found_to| found_to.ticket_id == to.ticket_id) {
+                found_tos.push(to);
+            }
+        }
         let result = ToFindResultDto {
             found_tos_count: found_tos.len(),
             missing_tos_count: missing_to_ids.len(),
             found_tos,
             missing_tos_ids: missing_to_ids,
             store_url: self.store_url.clone(),
+            found_source_identities,
+            missing_source_identities_count: missing_source_identities.len(),
+            missing_source_identities,
         };
         Ok(result)
     }
 
+    /// classify source identities into found, with the TOs they resolve to, and missing
+    async fn find_by_source_identities(&mut self, source_identities: &[ToSourceIdentity]) -> (Vec<TextualObject>, Vec<ToSourceIdentityMatch>, Vec<ToSourceIdentity>) {
+        let mut found_tos: Vec<TextualObject> = Vec::new();
+        let mut found_identities: Vec<ToSourceIdentityMatch> = Vec::new();
+        let mut missing_identities: Vec<ToSourceIdentity> = Vec::new();
+        let mut pool = self.get_pool().await;
+        for identity in source_identities.iter() {
+            // empty filters match any source name or id type
+            let source_name = identity.source_name.as_deref().filter(|name| !name.is_empty());
+            let source_id_type = identity.source_id_type.as_deref().filter(|id_type| !id_type.is_empty());
+            let tos = find_tos_by_source(pool.borrow_mut(), source_name, source_id_type, Some(&identity.source_id)).await;
+            if tos.is_empty() {
+                missing_identities.push(identity.clone());
+                continue;
+            }
+            found_identities.push(ToSourceIdentityMatch {
+                source_identity: identity.clone(),
+                ticket_ids: tos.iter().map(|to| to.ticket_id.clone()).collect(),
+            });
+            found_tos.extend(tos);
+        }
+        (found_tos, found_identities, missing_identities)
+    }
+
     /// This is higher level than find_tos_by_ticket_ids, for it classify the results into found and missing
     ///
-    async fn find_by_ticket_ids(&mut self, ticket_ids: &Vec<String>) -> (Vec<TextualObject>, Vec<String>) {
+    async fn find_by_ticket_ids(&mut self, ticket_ids: &[String]) -> (Vec<TextualObject>, Vec<String>) {
 // use find method to get all tos
         let mut found_tos: Vec<TextualObject> = Vec::new();
         let mut missing_to_ids: Vec<String> = vec![];
@@ -110,12 +225,67 @@ impl ToMachine {
             }
         }
         // use find method to get all tos
-        let matched_to_tickets = ToParser::scan_text_for_tickets(&scan_request.text, ToParserOption::default());
+        let parser_option = ToParserOption {
+            dialects: scan_request.dialects.clone(),
+            markdown: scan_request.markdown,
+            ..Default::default()
+        };
+        let parse_result = ToParser::scan_text_for_tickets(&scan_request.text, parser_option.clone());
+        let matched_to_tickets = parse_result.tickets;
+
+        // group the occurrences of each ticket id, in order of first occurrence;
+        // a ticket written without id has a random one, which refers to no TO
+        let mut occurrences: IndexMap<String, Vec<ToTicketOccurrence>> = IndexMap::new();
+        for ticket in matched_to_tickets.iter().filter(|ticket| !ticket.ticket_id.is_empty() && ticket.has_written_id(&parser_option)) {
+            occurrences.entry(ticket.ticket_id.clone()).or_default().push(ToTicketOccurrence::from(ticket));
+        }
+
+        let found_tos = self.find_by_ticket_ids(&occurrences.keys().cloned().collect::<Vec<_>>()).await;
+        let occurrences_of = |ticket_id: &String| ToScanTicketOccurrences {
+            ticket_id: ticket_id.clone(),
+            occurrences: occurrences.get(ticket_id).cloned().unwrap_or_default(),
+        };
+        let found_to_occurrences = found_tos.0.iter().map(|to| occurrences_of(&to.ticket_id)).collect();
+        let missing_to_occurrences = found_tos.1.iter().map(occurrences_of).collect();
 
-        let found_tos = self.find_by_ticket_ids(&matched_to_tickets.iter().map(
-            |ticket_id| ticket_id.ticket_id.to_string()
-        ).collect()).await;
+        // remove or replace the tickets at their positions; closing tickets go with the label of their span
+        let spans = &parse_result.spans;
+        let cleaned = match scan_request.clean_mode {
+            ToCleanMode::Strip => clean_text_with_spans(&scan_request.text, &matched_to_tickets, spans, |_| String::new(), |_| String::new()),
+            ToCleanMode::ReplaceWithMinimalTicket => clean_text_with_spans(
+                &scan_request.text, &matched_to_tickets, spans,
+                |ticket| match ticket.has_written_id(&parser_option) {
+                    true => print_minimal_ticket(&ticket.ticket_id, None),
+                    // a ticket without id has no minimal form, it is left as written
+                    false => raw_text(ticket),
+                },
+                |span| span.close.raw_text.clone(),
+            ),
+            ToCleanMode::ReplaceWithLabel => clean_text_with_spans(
+                &scan_request.text, &matched_to_tickets, spans,
+                |ticket| ticket_label(ticket, found_tos.0.iter().find(|to| to.ticket_id == ticket.ticket_id), &parser_option),
+                |_| String::new(),
+            ),
+        };
 
+        // compare each ticket with its stored TO
+        let mut stale_tickets = Vec::new();
+        if scan_request.check_stale {
+            for ticket in matched_to_tickets.iter() {
+                let found_to = match found_tos.0.iter().find(|to| to.ticket_id == ticket.ticket_id) {
+                    Some(found_to) => found_to,
+                    None => continue,
+                };
+                let differences = ticket.stale_differences(found_to);
+                if !differences.is_empty() {
+                    stale_tickets.push(ToStaleTicket {
+                        ticket_id: ticket.ticket_id.clone(),
+                        occurrence: ToTicketOccurrence::from(ticket),
+                        differences,
+                    });
+                }
+            }
+        }
 
         let result = ToScanResultDto {
             found_tos_count: found_tos.0.len(),
@@ -123,28 +293,296 @@ impl ToMachine {
             found_tos: found_tos.0,
             missing_tos_ids: found_tos.1,
             store_url: self.store_url.clone(),
-            cleaned_text: scan_request.text.clone(),
+            cleaned_text: cleaned.text,
+            source_map: cleaned.source_map,
+            found_to_occurrences,
+            missing_to_occurrences,
+            stale_tickets,
         };
 
         Ok(result)
 
     }
 
+    /// collect statistics about the store, e.g. counts by source, histograms and card coverage
+    pub async fn stats(&mut self) -> ToStatsDto {
+        let to_count = self.update_to_count().await;
+        let mut pool = self.get_pool().await;
+
+        // the file may not exist for non-sqlite stores
+        let database_file_size = std::fs::metadata(&self.store_url).map(|m| m.len()).unwrap_or(0);
+
+        ToStatsDto {
+            store_url: self.store_url.clone(),
+            store_info: self.store_info.clone(),
+            to_count,
+            count_by_source_name: count_textual_objects_group_by(pool.borrow_mut(), "source_name").await,
+            count_by_source_id_type: count_textual_objects_group_by(pool.borrow_mut(), "source_id_type").await,
+            created_histogram: count_textual_objects_by_day(pool.borrow_mut(), "created").await,
+            updated_histogram: count_textual_objects_by_day(pool.borrow_mut(), "updated").await,
+            average_json_size: average_json_size(pool.borrow_mut()).await,
+            cards_with_title: count_cards_with_title(pool.borrow_mut()).await,
+            cards_with_creators: count_cards_with_creators(pool.borrow_mut()).await,
+            database_file_size,
+        }
+    }
+
+}
+
+// rewrite tickets across a text
+impl ToMachine {
+    /// rewrite every ticket of a text as the mode asks, e.g. upgrade minimal tickets to full ones;
+    /// returns the new text with a unified diff from the original
+    pub async fn rewrite_tickets_in_text(&mut self, rewrite_request: &ToRewriteRequestDto) -> Result<ToRewriteResultDto, ToErrors> {
+        rewrite_request.validate()?;
+        let parser_option = ToParserOption {
+            dialects: rewrite_request.dialects.clone(),
+            markdown: rewrite_request.markdown,
+            ..Default::default()
+        };
+        let parse_result = ToParser::scan_text_for_tickets(&rewrite_request.text, parser_option.clone());
+        let tickets = parse_result.tickets;
+
+        // only upgrading and refreshing need the TOs
+        let (found_tos, missing_tos_ids) = if rewrite_request.mode.reads_store() {
+            // a ticket written without id has a random one, which refers to no TO
+            let mut ticket_ids: Vec<String> = Vec::new();
+            for ticket in tickets.iter().filter(|ticket| !ticket.ticket_id.is_empty() && ticket.has_written_id(&parser_option)) {
+                if !ticket_ids.contains(&ticket.ticket_id) {
+                    ticket_ids.push(ticket.ticket_id.clone());
+                }
+            }
+            self.find_by_ticket_ids(&ticket_ids).await
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        // new text of each rewritten ticket, by its offset in the text
+        let mut rewritten = Vec::new();
+        let mut new_texts: HashMap<usize, String> = HashMap::new();
+        for ticket in tickets.iter() {
+            let context = ToRewriteContext {
+                mode: rewrite_request.mode,
+                textual_object: found_tos.iter().find(|to| to.ticket_id == ticket.ticket_id),
+                id_remap: &rewrite_request.id_remap,
+                projection: &rewrite_request.projection,
+                parser_option: &parser_option,
+            };
+            let occurrence = ToTicketOccurrence::from(ticket);
+            match ticket.rewrite(&context) {
+                Some(new_text) if new_text != occurrence.raw_text => {
+                    new_texts.insert(occurrence.byte_offset, new_text.clone());
+                    rewritten.push(ToTicketRewrite {
+                        ticket_id: ticket.ticket_id.clone(),
+                        occurrence,
+                        new_text,
+                    });
+                }
+                _ => {}
+            }
+        }
+        // closing tickets follow their opening ticket
+        let close_context = ToRewriteContext {
+            mode: rewrite_request.mode,
+            textual_object: None,
+            id_remap: &rewrite_request.id_remap,
+            projection: &rewrite_request.projection,
+            parser_option: &parser_option,
+        };
+        let text = clean_text_with_spans(&rewrite_request.text, &tickets, &parse_result.spans, |ticket| {
+            let position = ticket.to_intext_option.as_ref();
+            position.and_then(|position| new_texts.get(&position.byte_offset).cloned())
+                .unwrap_or_else(|| position.map(|position| position.raw_text.clone()).unwrap_or_default())
+        }, |span| {
+            span.rewrite_close(&tickets[span.ticket_index], &close_context).unwrap_or_else(|| span.close.raw_text.clone())
+        }).text;
+
+        Ok(ToRewriteResultDto {
+            store_url: self.store_url.clone(),
+            diff: unified_diff(&rewrite_request.text, &text, "a/text", "b/text", 3),
+            text,
+            rewritten_count: rewritten.len(),
+            rewritten,
+            missing_tos_ids,
+        })
+    }
+
+    /// capture the tickets of a text written without id as new TOs, and write their new ids into the text
+    pub async fn capture_tos_from_text(&mut self, capture_request: &ToCaptureRequestDto) -> Result<ToCaptureResultDto, ToErrors> {
+        capture_request.validate()?;
+        let parser_option = ToParserOption {
+            dialects: capture_request.dialects.clone(),
+            markdown: capture_request.markdown,
+            ..Default::default()
+        };
+        let tickets = ToParser::scan_text_for_tickets(&capture_request.text, parser_option.clone()).tickets;
+
+        // one TO for each distinct set of values, in order of first occurrence
+        let mut add_tos_dto = ToAddManyDto {
+            store_dir: split_store_path(&self.store_url).0,
+            ..Default::default()
+        };
+        let mut to_index_of_values: HashMap<String, usize> = HashMap::new();
+        let mut captured_tickets: Vec<(&ToTicket, usize)> = Vec::new();
+        for ticket in tickets.iter().filter(|ticket| ticket.is_capturable(&parser_option)) {
+            let values = serde_json::to_string(&ticket.values).unwrap_or_default();
+            let to_index = *to_index_of_values.entry(values).or_insert_with(|| {
+                add_tos_dto.tos.push(ticket.to_add_dto(&capture_request.source_name));
+                add_tos_dto.tos.len() - 1
+            });
+            captured_tickets.push((ticket, to_index));
+        }
+        if captured_tickets.is_empty() {
+            return Ok(ToCaptureResultDto {
+                store_url: self.store_url.clone(),
+                text: capture_request.text.clone(),
+                diff: String::new(),
+                captured: Vec::new(),
+                receipt: None,
+            });
+        }
+
+        // the receipt lists the new TOs in the order they were added
+        let receipt = self.add_tos(add_tos_dto).await?;
+        let ticket_ids: Vec<String> = receipt.tos_stored.keys().cloned().collect();
+        let mut captured = Vec::new();
+        let mut new_texts: HashMap<usize, String> = HashMap::new();
+        for (ticket, to_index) in captured_tickets {
+            let ticket_id = &ticket_ids[to_index];
+            let new_text = if capture_request.write_minimal {
+                let mut minimal = ticket.clone();
+                minimal.ticket_id = ticket_id.clone();
+                minimal.to_marker = ticket.to_dialect.marker(&parser_option.to_marker).unwrap_or_default();
+                minimal.print_minimal()
+            } else {
+                ticket.print_captured(ticket_id, &parser_option)
+            };
+            let occurrence = ToTicketOccurrence::from(ticket);
+            new_texts.insert(occurrence.byte_offset, new_text.clone());
+            captured.push(ToTicketRewrite {
+                ticket_id: ticket_id.clone(),
+                occurrence,
+                new_text,
+            });
+        }
+        let text = clean_text(&capture_request.text, &tickets, |ticket| {
+            let position = ticket.to_intext_option.as_ref();
+            position.and_then(|position| new_texts.get(&position.byte_offset).cloned())
+                .unwrap_or_else(|| position.map(|position| position.raw_text.clone()).unwrap_or_default())
+        }).text;
+
+        Ok(ToCaptureResultDto {
+            store_url: self.store_url.clone(),
+            diff: unified_diff(&capture_request.text, &text, "a/text", "b/text", 3),
+            text,
+            captured,
+            receipt: Some(receipt),
+        })
+    }
+}
+
+// lint the tickets of a text
+impl ToMachine {
+    /// Report the problems of the tickets in a text, see `ToLintRule`: the problems found by the parser,
+    /// malformed tickets and keys, tickets that refer to another store, and tickets whose id is not in the store.
+    /// The report can be written as JSON or SARIF
+    pub async fn lint_text(&mut self, lint_request: &ToLintRequestDto) -> Result<ToLintReportDto, ToErrors> {
+        lint_request.validate()?;
+        let parser_option = ToParserOption {
+            dialects: lint_request.dialects.clone(),
+            markdown: lint_request.markdown,
+            ..Default::default()
+        };
+        let parse_result = ToParser::scan_text_for_tickets(&lint_request.text, parser_option.clone());
+        let mut findings = lint_parse_result(&lint_request.text, &parse_result, &parser_option);
+
+        let scan_result = self.find_tos_by_text(&ToScanRequestDto {
+            store_url: self.store_url.clone(),
+            text: lint_request.text.clone(),
+            clean_mode: ToCleanMode::default(),
+            dialects: lint_request.dialects.clone(),
+            markdown: lint_request.markdown,
+            check_stale: false,
+        }).await?;
+        for ticket in parse_result.tickets.iter() {
+            let position = match &ticket.to_intext_option {
+                Some(position) => position,
+                None => continue,
+            };
+            if let Some(store_url) = ticket.to_store_url.as_ref().filter(|store_url| **store_url != self.store_url) {
+                findings.push(ToLintFinding {
+                    ticket_id: Some(ticket.ticket_id.clone()),
+                    fix: Some(ToLintFix {
+                        description: "Add the TO to this store to find it here".to_string(),
+                        replacement: None,
+                    }),
+                    ..ToLintFinding::new(ToLintRule::OtherStore, format!("Ticket \"{}\" refers to the store \"{}\"", ticket.ticket_id, store_url), position)
+                });
+                continue;
+            }
+            if scan_result.missing_tos_ids.contains(&ticket.ticket_id) {
+                findings.push(ToLintFinding {
+                    ticket_id: Some(ticket.ticket_id.clone()),
+                    fix: Some(ToLintFix {
+                        description: "Correct the id, or add the TO to the store".to_string(),
+                        replacement: None,
+                    }),
+                    ..ToLintFinding::new(ToLintRule::MissingTo, format!("No TO with id \"{}\" in the store", ticket.ticket_id), position)
+                });
+            }
+        }
+        findings.sort_by_key(|finding| (finding.byte_offset, finding.rule.code()));
+
+        Ok(ToLintReportDto {
+            store_url: self.store_url.clone(),
+            source: lint_request.source.clone(),
+            findings,
+        })
+    }
+}
+
+// readable label of a ticket: the display label of its TO, as printed in labelled tickets,
+// else the label or title written in the ticket, else its id; a ticket without any of them is left as written
+fn ticket_label(ticket: &ToTicket, textual_object: Option<&TextualObject>, opt: &ToParserOption) -> String {
+    if let Some(label) = textual_object.and_then(|to| to.display_label()) {
+        return label;
+    }
+    let written_label = ticket.to_label.clone()
+        .or_else(|| ticket.values.get("title").map(|title| title.to_string()))
+        .filter(|label| !label.trim().is_empty());
+    match written_label {
+        Some(label) => label,
+        None if ticket.has_written_id(opt) => ticket.ticket_id.clone(),
+        None => raw_text(ticket),
+    }
+}
+
+// the ticket as written in the text
+fn raw_text(ticket: &ToTicket) -> String {
+    ticket.to_intext_option.as_ref().map(|position| position.raw_text.clone()).unwrap_or_default()
 }
 
 // test
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
     use std::fmt::Debug;
     use crate::db::db_op::join_db_path;
     use crate::enums::store_type::StoreType;
     use crate::error::error_message::ToErrorMessage;
     use crate::error::ToErrors;
     use crate::to::to_dtos::to_add_dto::ToAddManyDto;
-    use crate::to::to_dtos::to_find_dto::ToFindRequestDto;
+    use crate::to::to_dtos::to_capture_dto::ToCaptureRequestDto;
+    use crate::to::to_dtos::to_delete_dto::ToDeleteManyDto;
+    use crate::to::to_dtos::to_find_dto::{ToFindRequestDto, ToSourceIdentity};
+    use crate::to::to_dtos::to_lint_dto::ToLintRequestDto;
+    use crate::to::to_dtos::to_rewrite_dto::{ToRewriteMode, ToRewriteRequestDto};
+    use crate::to::to_dtos::to_scan_dto::{ToCleanMode, ToScanRequestDto};
+    use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
     use crate::to::to_struct::TextualObject;
     use crate::to_machine::to_machine_option::ToMachineOption;
     use crate::to_machine::to_machine_struct::ToMachine;
+    use crate::to_ticket::to_ticket_projection::ToTicketProjection;
     use crate::utils::get_random_test_database_dir::get_random_test_database_dir;
     use crate::utils::id_generator::generate_id;
 
@@ -252,6 +690,7 @@ mod test {
         let find_request_dto = ToFindRequestDto {
             ticket_ids: vec![to_1.ticket_id.clone(), to_2.ticket_id.clone(), to_3.ticket_id.clone()],
             store_url: join_db_path(&test_database_dir, &random_filename).to_string(),
+            source_identities: vec![],
         };
         // create TextualObjectMachine
         let mut textual_object_machine = ToMachine::new(
@@ -275,12 +714,12 @@ mod test {
         assert_eq!(result_found_one.found_tos.len(), 1);
         assert_eq!(result_found_one.found_tos_count, 1);
         assert_eq!(result_found_one.missing_tos_count, 2);
-        let first_found = result_found_one.found_tos.get(&to_1.ticket_id).unwrap();
+        let first_found = result_found_one.found_tos.iter().find(|to| to.ticket_id == to_1.ticket_id).unwrap();
         assert_eq!(first_found.ticket_id, to_1.ticket_id);
         // add three
         textual_object_machine.add_textual_object(&to_2).await;
         textual_object_machine.add_textual_object(&to_3).await;
-        let first_found_to = result_found_one.found_tos.get(&to_1.ticket_id).unwrap();
+        let first_found_to = result_found_one.found_tos.iter().find(|to| to.ticket_id == to_1.ticket_id).unwrap();
         assert_eq!(first_found_to.ticket_id, to_1.ticket_id);
         // search again
         let result_found_all_wrapped = textual_object_machine.find_tos_by_ticket_ids(&find_request_dto).await;
@@ -289,16 +728,65 @@ mod test {
         assert_eq!(result_found_all.missing_tos_ids.len(), 0);
         assert_eq!(result_found_all.found_tos.len(), 3);
         // check found tos
-        let first_found_to = result_found_all.found_tos.get(&to_1.ticket_id).unwrap();
+        let first_found_to = result_found_all.found_tos.iter().find(|to| to.ticket_id == to_1.ticket_id).unwrap();
         assert_eq!(first_found_to.ticket_id, to_1.ticket_id);
-        let second_found_to = result_found_all.found_tos.get(&to_2.ticket_id).unwrap();
+        let second_found_to = result_found_all.found_tos.iter().find(|to| to.ticket_id == to_2.ticket_id).unwrap();
         assert_eq!(second_found_to.ticket_id, to_2.ticket_id);
-        let third_found_to = result_found_all.found_tos.get(&to_3.ticket_id).unwrap();
+        let third_found_to = result_found_all.found_tos.iter().find(|to| to.ticket_id == to_3.ticket_id).unwrap();
         assert_eq!(third_found_to.ticket_id, to_3.ticket_id);
 
         // check result store url equals to machine store url
         assert_eq!(result_found_all.store_url, textual_object_machine.store_url);
     }
+    // test find by source identities
+    #[tokio::test]
+    async fn test_find_tos_by_source_identities() {
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let mut to_doi = TextualObject::get_sample();
+        to_doi.source_name = "Zotero".to_string();
+        to_doi.source_id_type = "DOI".to_string();
+        to_doi.source_id = "10.1000/182".to_string();
+        textual_object_machine.add_textual_object(&to_doi).await;
+
+        let doi = ToSourceIdentity {
+            source_name: None,
+            source_id_type: Some("DOI".to_string()),
+            source_id: "10.1000/182".to_string(),
+        };
+        let wrong_source = ToSourceIdentity {
+            source_name: Some("Other".to_string()),
+            ..doi.clone()
+        };
+        let find_request_dto = ToFindRequestDto {
+            store_url: textual_object_machine.store_url.clone(),
+            ticket_ids: vec![],
+            source_identities: vec![doi.clone(), wrong_source.clone()],
+        };
+        let result = textual_object_machine.find_tos_by_ticket_ids(&find_request_dto).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.found_tos[0].ticket_id, to_doi.ticket_id);
+        assert_eq!(result.found_source_identities.len(), 1);
+        assert_eq!(result.found_source_identities[0].source_identity, doi);
+        assert_eq!(result.found_source_identities[0].ticket_ids, vec![to_doi.ticket_id.clone()]);
+        assert_eq!(result.missing_source_identities, vec![wrong_source]);
+        assert_eq!(result.missing_source_identities_count, 1);
+        assert_eq!(result.missing_tos_count, 0);
+
+        // a TO found by ticket id and by source identity is returned once
+        let both_request_dto = ToFindRequestDto {
+            ticket_ids: vec![to_doi.ticket_id.clone()],
+            source_identities: vec![doi],
+            ..find_request_dto
+        };
+        let result = textual_object_machine.find_tos_by_ticket_ids(&both_request_dto).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+    }
+
     // test find request dto with invalid request
     #[tokio::test]
     async fn test_find_tos_by_ticket_ids_invalid() {
@@ -313,6 +801,7 @@ mod test {
         let find_request_dto = ToFindRequestDto {
             ticket_ids: vec![],
             store_url: join_db_path(&test_database_dir, &random_filename).to_string(),
+            source_identities: vec![],
         };
         // create TextualObjectMachine
         let mut textual_object_machine = ToMachine::new(
@@ -335,9 +824,354 @@ mod test {
         }
     }
 
-    // Todo, write test for scan request
+    // test delete_tos
+    #[tokio::test]
+    async fn test_delete_tos() {
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let mut to_a = TextualObject::get_sample();
+        to_a.source_name = "Zotero".to_string();
+        to_a.source_id = "a".to_string();
+        let mut to_b = TextualObject::get_sample();
+        to_b.source_name = "Zotero".to_string();
+        to_b.source_id = "b".to_string();
+        let mut to_c = TextualObject::get_sample();
+        to_c.source_name = "DOI".to_string();
+        to_c.source_id = "b".to_string();
+        textual_object_machine.add_textual_object(&to_a).await;
+        textual_object_machine.add_textual_object(&to_b).await;
+        textual_object_machine.add_textual_object(&to_c).await;
+
+        let delete_dto = ToDeleteManyDto {
+            store_url: textual_object_machine.store_url.clone(),
+            ticket_ids: vec![to_a.ticket_id.clone(), "missing".to_string()],
+            source_ids: vec!["b".to_string(), "nope".to_string()],
+            source_name: Some("Zotero".to_string()),
+            protected_ticket_ids: vec![to_b.ticket_id.clone()],
+        };
+        let receipt = textual_object_machine.delete_tos(&delete_dto).await.unwrap();
+        assert_eq!(receipt.deleted_ticket_ids, vec![to_a.ticket_id.clone()]);
+        assert_eq!(receipt.not_found_ticket_ids, vec!["missing".to_string()]);
+        assert_eq!(receipt.not_found_source_ids, vec!["nope".to_string()]);
+        assert_eq!(receipt.protected_ticket_ids, vec![to_b.ticket_id.clone()]);
+        assert_eq!(receipt.total_tos_deleted, 1);
+        // the DOI item with the same source id belongs to another source
+        assert!(textual_object_machine.find(&to_c.ticket_id).await.is_some());
+        assert_eq!(textual_object_machine.to_count, 2);
+
+        // invalid request
+        let invalid_dto = ToDeleteManyDto {
+            store_url: textual_object_machine.store_url.clone(),
+            ..Default::default()
+        };
+        match textual_object_machine.delete_tos(&invalid_dto).await {
+            Err(ToErrors::DeleteManyRequestError(e)) => {
+                assert_eq!(e.message, ToErrorMessage::DeleteRequestDtoNothingSelected.to_string());
+            }
+            _ => panic!("Expected error"),
+        }
+    }
+
+    // test scan request
     #[tokio::test]
     async fn test_scan_request() {
-        
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let mut to = TextualObject::get_sample();
+        to.json = sqlx::types::Json(serde_json::json!({"title": "Discipline and Punish"}));
+        textual_object_machine.add_textual_object(&to).await;
+
+        let text = format!("As argued in [[id:{} | p: 12]], see also [[id:missing]].", to.ticket_id);
+        let mut scan_request = ToScanRequestDto {
+            store_url: textual_object_machine.store_url.clone(),
+            text: text.clone(),
+            clean_mode: ToCleanMode::Strip,
+            dialects: vec![ToMarkerDialect::DoubleSquare],
+            markdown: false,
+            check_stale: false,
+        };
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.missing_tos_ids, vec!["missing".to_string()]);
+        assert_eq!(result.cleaned_text, "As argued in, see also.");
+        assert_eq!(result.original_offset(result.cleaned_text.find(", see").unwrap()), text.find("]], see").unwrap() + 2);
+
+        scan_request.clean_mode = ToCleanMode::ReplaceWithLabel;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, "As argued in Discipline and Punish, see also missing.");
+
+        scan_request.clean_mode = ToCleanMode::ReplaceWithMinimalTicket;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, format!("As argued in {}, see also [[id: missing]].", to.ticket_minimal));
+
+        // the closing ticket of a span is removed with its opening ticket, or kept with the minimal ticket
+        scan_request.text = format!("See [[id:{0}]] the passage [[/id:{0}]] here.", to.ticket_id);
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, format!("See {} the passage [[/id:{}]] here.", to.ticket_minimal, to.ticket_id));
+        scan_request.clean_mode = ToCleanMode::ReplaceWithLabel;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, "See Discipline and Punish the passage here.");
+        scan_request.clean_mode = ToCleanMode::Strip;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, "See the passage here.");
+        assert_eq!(result.original_offset(result.cleaned_text.find(" here").unwrap()), scan_request.text.find(" here").unwrap());
+        scan_request.text = text.clone();
+        scan_request.clean_mode = ToCleanMode::ReplaceWithMinimalTicket;
+
+        // a TO cited twice is found once, with both occurrences
+        scan_request.text = format!("{}\nand again [[id:{}]] [[id:missing]]", text, to.ticket_id);
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.missing_tos_ids, vec!["missing".to_string()]);
+        let occurrences = &result.found_to_occurrences[0].occurrences;
+        assert_eq!(result.found_to_occurrences[0].ticket_id, to.ticket_id);
+        assert_eq!(occurrences.len(), 2);
+        assert_eq!((occurrences[0].line, occurrences[0].column), (0, "As argued in ".len()));
+        assert_eq!(occurrences[0].values.get("p").unwrap(), &serde_json::json!(12));
+        assert_eq!((occurrences[1].line, occurrences[1].column), (1, "and again ".len()));
+        assert_eq!(occurrences[1].raw_text, format!("[[id:{}]]", to.ticket_id));
+        assert!(occurrences[1].values.is_empty());
+        assert_eq!(result.missing_to_occurrences[0].ticket_id, "missing");
+        assert_eq!(result.missing_to_occurrences[0].occurrences.len(), 2);
+
+        // tickets written without id refer to no TO, they are neither found nor missing
+        scan_request.text = format!("[[title: Draft]] [[id:{}]] [[id:missing]] [[title: Notes | p: 3]]", to.ticket_id);
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.missing_tos_ids, vec!["missing".to_string()]);
+        let missing: Vec<&str> = result.missing_to_occurrences.iter().map(|missing| missing.ticket_id.as_str()).collect();
+        assert_eq!(missing, vec!["missing"]);
+        // and they are left as written rather than given a minimal ticket
+        assert_eq!(result.cleaned_text, format!("[[title: Draft]] {} [[id: missing]] [[title: Notes | p: 3]]", to.ticket_minimal));
+        scan_request.clean_mode = ToCleanMode::ReplaceWithLabel;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, "Draft Discipline and Punish missing Notes");
+        scan_request.text = "[[p: 3]]".to_string();
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.cleaned_text, "[[p: 3]]");
+        scan_request.clean_mode = ToCleanMode::ReplaceWithMinimalTicket;
+
+        // tickets typed with full-width brackets are only found when asked for
+        scan_request.text = format!("引用【【id:{}】】", to.ticket_id);
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 0);
+        scan_request.dialects = ToMarkerDialect::all();
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.found_to_occurrences[0].occurrences[0].dialect, ToMarkerDialect::FullWidthLenticular);
+
+        // tickets in markdown code are not reported missing
+        scan_request.text = format!("[[id:{}]] as in `[[id:missing]]`", to.ticket_id);
+        scan_request.markdown = true;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.found_tos_count, 1);
+        assert_eq!(result.missing_tos_count, 0);
+
+        // tickets older than their TO, or with other values, are reported stale
+        scan_request.text = format!("[[id:{0}]] and\n[[id:{0} | title: Surveiller et punir | p: 12 | updated: 2000-01-01 00:00:00]]", to.ticket_id);
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert!(result.stale_tickets.is_empty());
+        scan_request.check_stale = true;
+        let result = textual_object_machine.find_tos_by_text(&scan_request).await.unwrap();
+        assert_eq!(result.stale_tickets.len(), 1);
+        let stale = &result.stale_tickets[0];
+        assert_eq!(stale.occurrence.line, 1);
+        let fields: Vec<&str> = stale.differences.iter().map(|difference| difference.field.as_str()).collect();
+        assert_eq!(fields, vec!["updated", "title"]);
+        assert_eq!(stale.differences[1].in_store, Some(serde_json::json!("Discipline and Punish")));
+    }
+
+    // test rewriting the tickets of a text
+    #[tokio::test]
+    async fn test_rewrite_request() {
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let mut to = TextualObject::get_sample();
+        to.json = sqlx::types::Json(serde_json::json!({"title": "Discipline and Punish", "year": 1975}));
+        textual_object_machine.add_textual_object(&to).await;
+
+        let text = format!("Intro.\nAs argued in [[id:{}]],\nsee also [[id:missing]] and [[title:Draft]].\n", to.ticket_id);
+        let mut rewrite_request = ToRewriteRequestDto {
+            store_url: textual_object_machine.store_url.clone(),
+            text: text.clone(),
+            mode: ToRewriteMode::Upgrade,
+            id_remap: HashMap::new(),
+            projection: ToTicketProjection::of_paths(&["title"]),
+            dialects: vec![ToMarkerDialect::DoubleSquare],
+            markdown: false,
+        };
+        let result = textual_object_machine.rewrite_tickets_in_text(&rewrite_request).await.unwrap();
+        assert_eq!(result.rewritten_count, 1);
+        assert_eq!(result.missing_tos_ids, vec!["missing".to_string()]);
+        let upgraded = &result.rewritten[0].new_text;
+        assert!(upgraded.starts_with(&format!("[[id: {} | title: Discipline and Punish | updated: ", to.ticket_id)));
+        assert_eq!(result.text, text.replace(&format!("[[id:{}]]", to.ticket_id), upgraded));
+        assert!(result.diff.starts_with("--- a/text\n+++ b/text\n@@ -1,3 +1,3 @@\n Intro.\n-As argued in"));
+
+        // downgrading the upgraded text gives minimal tickets back
+        rewrite_request.text = result.text.clone();
+        rewrite_request.mode = ToRewriteMode::Downgrade;
+        let result = textual_object_machine.rewrite_tickets_in_text(&rewrite_request).await.unwrap();
+        assert_eq!(result.text, text.replace(&format!("[[id:{}]]", to.ticket_id), &to.ticket_minimal));
+
+        // remapping needs a remap
+        rewrite_request.mode = ToRewriteMode::Remap;
+        assert!(textual_object_machine.rewrite_tickets_in_text(&rewrite_request).await.is_err());
+        rewrite_request.id_remap.insert("missing".to_string(), "found".to_string());
+        let result = textual_object_machine.rewrite_tickets_in_text(&rewrite_request).await.unwrap();
+        assert_eq!(result.rewritten_count, 1);
+        assert!(result.text.ends_with("see also [[id: found]] and [[title:Draft]].\n"));
+        assert!(result.diff.contains("-see also [[id:missing]] and [[title:Draft]].\n+see also [[id: found]] and [[title:Draft]].\n"));
+
+        // the closing ticket of a span is remapped with its opening ticket
+        rewrite_request.text = "[[id:missing]]the passage[[/id:missing]] and [[id:other]]more[[/id:other]]".to_string();
+        let result = textual_object_machine.rewrite_tickets_in_text(&rewrite_request).await.unwrap();
+        assert_eq!(result.text, "[[id: found]]the passage[[/id: found]] and [[id:other]]more[[/id:other]]");
+    }
+
+    // test capturing tickets without id as new TOs
+    #[tokio::test]
+    async fn test_capture_request() {
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let text = "As in [[title:Some Book|author:X]] and [[id:known]],\nagain [[title:Some Book|author:X]] and [[title:Other|year:1999]].";
+        let mut capture_request = ToCaptureRequestDto {
+            store_url: textual_object_machine.store_url.clone(),
+            text: text.to_string(),
+            source_name: "Draft".to_string(),
+            write_minimal: false,
+            dialects: vec![ToMarkerDialect::DoubleSquare],
+            markdown: false,
+        };
+        let result = textual_object_machine.capture_tos_from_text(&capture_request).await.unwrap();
+        let receipt = result.receipt.unwrap();
+        // the same ticket written twice is one TO
+        assert_eq!(receipt.total_tos_stored, 2);
+        assert_eq!(result.captured.len(), 3);
+        let ids: Vec<&String> = receipt.tos_stored.keys().collect();
+        assert_eq!(result.captured[0].ticket_id, *ids[0]);
+        assert_eq!(result.captured[1].ticket_id, *ids[0]);
+        assert_eq!(result.captured[2].ticket_id, *ids[1]);
+        assert_eq!(result.text, format!(
+            "As in [[id: {0} | title: Some Book | author: X]] and [[id:known]],\nagain [[id: {0} | title: Some Book | author: X]] and [[id: {1} | title: Other | year: 1999]].",
+            ids[0], ids[1],
+        ));
+        assert!(result.diff.starts_with("--- a/text\n+++ b/text\n@@ -1,2 +1,2 @@\n"));
+
+        // the new TOs are in the store with the values of their tickets
+        let stored = textual_object_machine.find(ids[1]).await.unwrap();
+        assert_eq!(stored.source_name, "Draft");
+        assert_eq!(stored.json.0, serde_json::json!({"title": "Other", "year": 1999}));
+
+        // a text with ids only captures nothing
+        capture_request.text = result.text.clone();
+        capture_request.write_minimal = true;
+        let result = textual_object_machine.capture_tos_from_text(&capture_request).await.unwrap();
+        assert!(result.receipt.is_none());
+        assert_eq!(result.text, capture_request.text);
+        assert!(result.diff.is_empty());
+
+        capture_request.text = "[[title:Third]]".to_string();
+        let result = textual_object_machine.capture_tos_from_text(&capture_request).await.unwrap();
+        assert_eq!(result.text, format!("[[id: {}]]", result.captured[0].ticket_id));
+    }
+
+    // test linting the tickets of a text against the store
+    #[tokio::test]
+    async fn test_lint_request() {
+        let mut textual_object_machine = ToMachine::new(
+            &get_random_test_database_dir(), StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+        let to = TextualObject::get_sample();
+        textual_object_machine.add_textual_object(&to).await;
+
+        let text = format!(
+            "Known [[id:{0}]], missing [[id:missing]] and [[ id : {0} ]],\n[[id:x|store_id:other.db]] [[title:Draft]] [[id:{0}|p:1|p:2|id:{0}]] [[id:oops",
+            to.ticket_id,
+        );
+        let mut lint_request = ToLintRequestDto {
+            store_url: textual_object_machine.store_url.clone(),
+            text: text.clone(),
+            source: "chapter.md".to_string(),
+            dialects: vec![ToMarkerDialect::DoubleSquare],
+            markdown: false,
+        };
+        let report = textual_object_machine.lint_text(&lint_request).await.unwrap();
+        let codes: Vec<&str> = report.findings.iter().map(|finding| finding.code.as_str()).collect();
+        assert_eq!(codes, vec!["TO009", "TO003", "TO008", "TO006", "TO001"]);
+        assert!(report.has_errors());
+        let missing = &report.findings[0];
+        assert_eq!(missing.ticket_id.as_deref(), Some("missing"));
+        assert_eq!((missing.line, missing.char_column), (0, text.find("[[id:missing").unwrap()));
+        assert_eq!(report.findings[2].message, "Ticket \"x\" refers to the store \"other.db\"");
+
+        // both reports have every finding
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["findings"].as_array().unwrap().len(), 5);
+        let sarif = report.to_sarif();
+        assert_eq!(sarif["runs"][0]["results"].as_array().unwrap().len(), 5);
+        assert_eq!(sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "chapter.md");
+
+        // a text without problems
+        lint_request.text = format!("[[id: {}]]", to.ticket_id);
+        let report = textual_object_machine.lint_text(&lint_request).await.unwrap();
+        assert!(report.findings.is_empty());
+
+        lint_request.text = String::new();
+        match textual_object_machine.lint_text(&lint_request).await {
+            Err(ToErrors::FindRequestError(e)) => assert_eq!(e.message, ToErrorMessage::ScanRequestDtoNoText.to_string()),
+            _ => panic!("Expected error"),
+        }
+    }
+
+    // test stats
+    #[tokio::test]
+    async fn test_stats() {
+        let add_tos_dto = ToAddManyDto::sample();
+        let mut textual_object_machine = ToMachine::new(
+            &add_tos_dto.store_dir, StoreType::SQLITE, Some(ToMachineOption {
+                use_random_file_name: true,
+                ..Default::default()
+            }),
+        ).await;
+
+        // empty store
+        let empty_stats = textual_object_machine.stats().await;
+        assert_eq!(empty_stats.to_count, 0);
+        assert_eq!(empty_stats.count_by_source_name.len(), 0);
+        assert_eq!(empty_stats.average_json_size, 0.0);
+
+        textual_object_machine.add_tos(add_tos_dto.clone()).await.unwrap();
+        let stats = textual_object_machine.stats().await;
+        assert_eq!(stats.to_count, add_tos_dto.tos.len() as i64);
+        assert_eq!(stats.count_by_source_name.get("source_name_value").unwrap(), &(add_tos_dto.tos.len() as i64));
+        assert_eq!(stats.count_by_source_id_type.get("source_id_type_value").unwrap(), &(add_tos_dto.tos.len() as i64));
+        assert_eq!(stats.created_histogram.values().sum::<i64>(), add_tos_dto.tos.len() as i64);
+        assert_eq!(stats.updated_histogram.values().sum::<i64>(), add_tos_dto.tos.len() as i64);
+        assert!(stats.average_json_size > 0.0);
+        // sample cards are empty
+        assert_eq!(stats.cards_with_title, 0);
+        assert_eq!(stats.cards_with_creators, 0);
+        assert!(stats.database_file_size > 0);
+        assert_eq!(stats.store_url, textual_object_machine.store_url);
     }
 }
\ No newline at end of file
@@ -3,12 +3,18 @@ use std::path::PathBuf;
 
 use sqlx::{Pool, Sqlite};
 use sqlx::pool::PoolConnection;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::Sender;
 
 use crate::db::db_op::{connect_to_database, initialize_database, join_db_path};
 use crate::enums::store_type::StoreType;
+use crate::error::ToErrors;
 use crate::to::to_dtos::to_add_dto::ToAddManyDto;
 use crate::to::to_dtos::to_find_dto::ToFindRequestDto;
+use crate::to_machine::to_machine_event::ToEvent;
 use crate::to_machine::to_machine_option::ToMachineOption;
+use crate::to_template::to_template_format::ToTemplateFormat;
+use crate::to_template::to_template_set::ToTemplateSet;
 use crate::utils::id_generator::generate_id;
 use crate::utils::split_store_path::split_store_path;
 
@@ -29,6 +35,9 @@ pub struct ToMachine {
 
     // pool
     pub(crate) pool: Option<Pool<Sqlite>>,
+
+    // publisher of change events, see `subscribe`
+    pub(crate) event_sender: Sender<ToEvent>,
 }
 
 
@@ -66,13 +75,17 @@ impl ToMachine {
             }
         }
 
+        let opt = input_opt.unwrap_or_default();
+        let (event_sender, _) = broadcast::channel(opt.event_capacity.max(1));
+
         // instantiate an temporary object
         let mut tom = ToMachine {
             store_type,
             store_url: String::new(),
-            store_info: input_opt.unwrap_or(ToMachineOption::default()).store_info.clone().unwrap_or("".to_string()),
+            store_info: opt.store_info.clone().unwrap_or("".to_string()),
             to_count,
             pool: None,
+            event_sender,
         };
 
         // initialize db and complete temporary object information
@@ -140,6 +153,10 @@ impl ToMachine {
     pub fn set_to_count(&mut self, to_count: i64) {
         self.to_count = to_count;
     }
+    /// templates of the store, read from the `to_templates` directory next to the store file
+    pub fn get_templates(&self, format: ToTemplateFormat) -> Result<ToTemplateSet, ToErrors> {
+        ToTemplateSet::for_store(&self.store_url, format)
+    }
 }
 
 // implement uitility functions for TextualObjectMachine
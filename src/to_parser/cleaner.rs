@@ -0,0 +1,167 @@
+use crate::to::to_dtos::to_scan_dto::ToSourceMapSegment;
+use crate::to_parser::parser_span::ToTicketSpan;
+use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+/// Text with its tickets removed or replaced, and the map back to the original text
+pub struct ToCleanedText {
+    pub text: String,
+    pub source_map: Vec<ToSourceMapSegment>,
+}
+
+impl ToCleanedText {
+    // copy text that is not part of a ticket
+    fn push_unchanged(&mut self, text: &str, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        self.source_map.push(ToSourceMapSegment {
+            cleaned_offset: self.text.len(),
+            cleaned_length: end - start,
+            original_offset: start,
+            original_length: end - start,
+            ticket_id: None,
+        });
+        self.text.push_str(&text[start..end]);
+    }
+}
+
+/// Replace the tickets found in `text` by a scan with `replacement(ticket)`, using their recorded positions.
+/// An empty replacement strips the ticket together with the spaces before it when it stands between words.
+/// Tickets without a position, or not matching the text at their position, are left as they are.
+pub fn clean_text<F: Fn(&ToTicket) -> String>(text: &str, tickets: &[ToTicket], replacement: F) -> ToCleanedText {
+    clean_text_with_spans(text, tickets, &[], replacement, |span| span.close.raw_text.clone())
+}
+
+/// Like `clean_text`, with the closing tickets of the spans of the scan replaced by `close_replacement(span)`
+pub fn clean_text_with_spans<F, G>(text: &str, tickets: &[ToTicket], spans: &[ToTicketSpan], replacement: F, close_replacement: G) -> ToCleanedText
+    where F: Fn(&ToTicket) -> String, G: Fn(&ToTicketSpan) -> String {
+    // position, id and replacement of each ticket and closing ticket, in order of the text
+    let mut replaced: Vec<(&ToTicketPositionInfo, &str, String)> = tickets.iter()
+        .filter_map(|ticket| ticket.to_intext_option.as_ref().map(|position| (position, ticket.ticket_id.as_str(), replacement(ticket))))
+        .chain(spans.iter().map(|span| (&span.close, span.ticket_id.as_str(), close_replacement(span))))
+        .collect();
+    replaced.sort_by_key(|(position, _, _)| position.byte_offset);
+
+    let mut cleaned = ToCleanedText {
+        text: String::with_capacity(text.len()),
+        source_map: Vec::new(),
+    };
+    let mut copied_until = 0;
+    for (position, ticket_id, replacement) in replaced {
+        let (mut start, end) = (position.byte_offset, position.end_byte_offset);
+        if start < copied_until || text.get(start..end) != Some(position.raw_text.as_str()) {
+            continue;
+        }
+        if replacement.is_empty() {
+            start = strip_start(text, copied_until, start, end);
+        }
+        cleaned.push_unchanged(text, copied_until, start);
+        cleaned.source_map.push(ToSourceMapSegment {
+            cleaned_offset: cleaned.text.len(),
+            cleaned_length: replacement.len(),
+            original_offset: start,
+            original_length: end - start,
+            ticket_id: Some(ticket_id.to_string()),
+        });
+        cleaned.text.push_str(&replacement);
+        copied_until = end;
+    }
+    cleaned.push_unchanged(text, copied_until, text.len());
+    cleaned
+}
+
+// start of a stripped ticket, moved before the spaces in front of it when a space or punctuation follows,
+// so that "a [[id:1]] b" becomes "a b" and "a [[id:1]]." becomes "a."
+fn strip_start(text: &str, min_start: usize, start: usize, end: usize) -> usize {
+    let followed_by_break = match text[end..].chars().next() {
+        Some(c) => c.is_whitespace() || c.is_ascii_punctuation(),
+        None => true,
+    };
+    if !followed_by_break {
+        return start;
+    }
+    min_start + text[min_start..start].trim_end_matches([' ', '\t']).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::to::to_dtos::to_scan_dto::ToScanResultDto;
+    use crate::to_parser::parser::ToParser;
+    use crate::to_parser::parser_option::ToParserOption;
+    use crate::to_ticket::to_ticket_utils::print_minimal_ticket;
+
+    use super::*;
+
+    const TEXT: &str = "As shown [[id:1 | title: Punish]] and in [[id:2\n  | title: Prison]].\n[[id:3]] ends here";
+
+    fn scan_result(cleaned: ToCleanedText) -> ToScanResultDto {
+        ToScanResultDto {
+            store_url: String::new(),
+            found_tos: vec![],
+            found_tos_count: 0,
+            missing_tos_ids: vec![],
+            missing_tos_count: 0,
+            cleaned_text: cleaned.text,
+            source_map: cleaned.source_map,
+            found_to_occurrences: vec![],
+            missing_to_occurrences: vec![],
+            stale_tickets: vec![],
+        }
+    }
+
+    #[test]
+    fn test_strip() {
+        let tickets = ToParser::scan_text_for_tickets(TEXT, ToParserOption::default()).tickets;
+        let cleaned = clean_text(TEXT, &tickets, |_| String::new());
+        assert_eq!(cleaned.text, "As shown and in.\n ends here");
+        let result = scan_result(cleaned);
+        // "and" and "ends" are found at their original offsets
+        let and = result.cleaned_text.find("and").unwrap();
+        assert_eq!(result.original_offset(and), TEXT.find("and").unwrap());
+        let ends = result.cleaned_text.find("ends").unwrap();
+        assert_eq!(result.original_offset(ends), TEXT.find("ends").unwrap());
+    }
+
+    #[test]
+    fn test_replace() {
+        let tickets = ToParser::scan_text_for_tickets(TEXT, ToParserOption::default()).tickets;
+        let cleaned = clean_text(TEXT, &tickets, |ticket| print_minimal_ticket(&ticket.ticket_id, None));
+        assert_eq!(cleaned.text, "As shown [[id: 1]] and in [[id: 2]].\n[[id: 3]] ends here");
+
+        let cleaned = clean_text(TEXT, &tickets, |ticket| ticket.values.get("title").map(|title| title.to_string()).unwrap_or_else(|| ticket.ticket_id.clone()));
+        assert_eq!(cleaned.text, "As shown Punish and in Prison.\n3 ends here");
+        let result = scan_result(cleaned);
+        assert_eq!(result.source_map.len(), 7);
+        assert_eq!(result.source_map[1].ticket_id, Some("1".to_string()));
+        // inside a label is the start of its ticket
+        let prison = result.cleaned_text.find("rison").unwrap();
+        assert_eq!(result.original_offset(prison), TEXT.find("[[id:2").unwrap());
+        assert_eq!(result.original_offset(result.cleaned_text.len()), TEXT.len());
+    }
+
+    #[test]
+    fn test_spans() {
+        let text = "A [[id:1]]marked passage[[/id:1]] and [[id:2]] here";
+        let result = ToParser::scan_text_for_tickets(text, ToParserOption::default());
+        let cleaned = clean_text_with_spans(text, &result.tickets, &result.spans, |_| String::new(), |_| String::new());
+        assert_eq!(cleaned.text, "A marked passage and here");
+        let result_dto = scan_result(cleaned);
+        assert_eq!(result_dto.source_map[3].ticket_id, Some("1".to_string()));
+        let and = result_dto.cleaned_text.find("and").unwrap();
+        assert_eq!(result_dto.original_offset(and), text.find("and").unwrap());
+
+        // without the spans, closing tickets are left as they are
+        let cleaned = clean_text(text, &result.tickets, |_| String::new());
+        assert_eq!(cleaned.text, "A marked passage[[/id:1]] and here");
+    }
+
+    #[test]
+    fn test_ticket_without_matching_position_is_kept() {
+        let mut tickets = ToParser::scan_text_for_tickets(TEXT, ToParserOption::default()).tickets;
+        tickets[0].to_intext_option.as_mut().unwrap().byte_offset += 1;
+        tickets[1].to_intext_option = None;
+        let cleaned = clean_text(TEXT, &tickets, |_| String::new());
+        assert_eq!(cleaned.text, TEXT.replace("\n[[id:3]]", "\n"));
+    }
+}
@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+/// Finds the parts of a markdown text that are not prose: front matter, fenced code blocks, inline code and HTML comments.
+/// Lines are given one at a time, in order, so that texts can be read line by line.
+#[derive(Default)]
+pub struct ToMarkdownMask {
+    lines_seen: usize,
+    in_front_matter: bool,
+    // fence character and length of the open code block
+    open_fence: Option<(char, usize)>,
+    in_comment: bool,
+}
+
+impl ToMarkdownMask {
+    /// byte ranges of a text that are not prose
+    pub fn excluded_ranges(text: &str) -> Vec<Range<usize>> {
+        let mut mask = ToMarkdownMask::default();
+        let mut excluded = Vec::new();
+        let mut line_start = 0;
+        for line in text.split_inclusive('\n') {
+            excluded.extend(mask.push_line(line, line_start));
+            line_start += line.len();
+        }
+        excluded
+    }
+
+    /// byte ranges of the next line, with its line break, that are not prose; `line_start` is the offset of the line in the text
+    pub fn push_line(&mut self, line: &str, line_start: usize) -> Vec<Range<usize>> {
+        let first_line = self.lines_seen == 0;
+        self.lines_seen += 1;
+        let line_range = line_start..line_start + line.len();
+        let whole_line = vec![line_range];
+        let content = line.trim_end_matches(['\n', '\r']);
+
+        // front matter: between --- on the first line and the next --- or ...
+        if first_line && content.trim_end() == "---" {
+            self.in_front_matter = true;
+            return whole_line;
+        }
+        if self.in_front_matter {
+            if content.trim_end() == "---" || content.trim_end() == "..." {
+                self.in_front_matter = false;
+            }
+            return whole_line;
+        }
+
+        // fenced code blocks, opened and closed by ``` or ~~~ indented by at most 3 spaces
+        let fence = fence_of(content);
+        match (self.open_fence, fence) {
+            (Some((open_char, open_length)), Some((fence_char, fence_length, info))) => {
+                if fence_char == open_char && fence_length >= open_length && info.trim().is_empty() {
+                    self.open_fence = None;
+                }
+                return whole_line;
+            }
+            (Some(_), None) => return whole_line,
+            (None, Some((fence_char, fence_length, _))) => {
+                self.open_fence = Some((fence_char, fence_length));
+                return whole_line;
+            }
+            (None, None) => {}
+        }
+
+        self.inline_ranges(content, line_start)
+    }
+
+    // HTML comments and code spans within a line of prose
+    fn inline_ranges(&mut self, content: &str, line_start: usize) -> Vec<Range<usize>> {
+        let mut excluded = Vec::new();
+        let mut position = 0;
+        if self.in_comment {
+            match content.find("-->") {
+                Some(end) => {
+                    self.in_comment = false;
+                    position = end + 3;
+                    excluded.push(line_start..line_start + position);
+                }
+                None => {
+                    excluded.push(line_start..line_start + content.len());
+                    return excluded;
+                }
+            }
+        }
+        while position < content.len() {
+            let rest = &content[position..];
+            let next_code = rest.find('`');
+            let next_comment = rest.find("<!--");
+            match (next_code, next_comment) {
+                (_, Some(comment)) if comment < next_code.unwrap_or(usize::MAX) => {
+                    let start = position + comment;
+                    match content[start + 4..].find("-->") {
+                        Some(end) => {
+                            position = start + 4 + end + 3;
+                            excluded.push(line_start + start..line_start + position);
+                        }
+                        None => {
+                            // the comment goes on in the next lines
+                            self.in_comment = true;
+                            excluded.push(line_start + start..line_start + content.len());
+                            position = content.len();
+                        }
+                    }
+                }
+                (Some(code), _) => {
+                    let start = position + code;
+                    let run = backtick_run(&content[start..]);
+                    match find_closing_run(&content[start + run..], run) {
+                        Some(end) => {
+                            position = start + run + end + run;
+                            excluded.push(line_start + start..line_start + position);
+                        }
+                        // an unmatched backtick run is literal text
+                        None => position = start + run,
+                    }
+                }
+                _ => break,
+            }
+        }
+        excluded
+    }
+}
+
+// fence character, fence length and info string of a fence line
+fn fence_of(line: &str) -> Option<(char, usize, &str)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let fence_char = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == fence_char).count();
+    if length < 3 {
+        return None;
+    }
+    Some((fence_char, length, &trimmed[length..]))
+}
+
+// number of backticks at the start of the text
+fn backtick_run(text: &str) -> usize {
+    text.chars().take_while(|c| *c == '`').count()
+}
+
+// offset of the next run of exactly `length` backticks
+fn find_closing_run(text: &str, length: usize) -> Option<usize> {
+    let mut position = 0;
+    while let Some(found) = text[position..].find('`') {
+        let start = position + found;
+        let run = backtick_run(&text[start..]);
+        if run == length {
+            return Some(start);
+        }
+        position = start + run;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excluded_texts(text: &str) -> Vec<&str> {
+        ToMarkdownMask::excluded_ranges(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_front_matter_and_fences() {
+        let text = "---\ntitle: a\n---\nprose\n```rust\ncode\n```\n~~~~\n```\n~~~~\nend";
+        assert_eq!(excluded_texts(text), vec![
+            "---\n", "title: a\n", "---\n",
+            "```rust\n", "code\n", "```\n",
+            "~~~~\n", "```\n", "~~~~\n",
+        ]);
+    }
+
+    #[test]
+    fn test_inline_code_and_comments() {
+        let text = "a `code` b ``x ` y`` c ` d <!-- e --> f <!-- g\nh\ni --> j";
+        assert_eq!(excluded_texts(text), vec!["`code`", "``x ` y``", "<!-- e -->", "<!-- g", "h", "i -->"]);
+    }
+
+    #[test]
+    fn test_dashes_later_are_prose() {
+        assert!(ToMarkdownMask::excluded_ranges("prose\n---\nmore").is_empty());
+    }
+}
@@ -1,2 +1,7 @@
 pub mod parser;
-pub mod parser_option;
\ No newline at end of file
+pub mod parser_option;
+pub mod parser_diagnostic;
+pub mod parser_span;
+pub mod scanner;
+pub mod cleaner;
+pub mod markdown;
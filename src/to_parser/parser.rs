@@ -1,8 +1,6 @@
-use regex::{escape, Regex};
-
+use crate::to_parser::parser_diagnostic::ToParseResult;
 use crate::to_parser::parser_option::ToParserOption;
-use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
-use crate::to_ticket::to_ticket_struct::ToTicket;
+use crate::to_parser::scanner::ToScanner;
 
 
 /// Parser to scan text for tickets
@@ -15,27 +13,12 @@ impl ToParser {
     /// * `text` - raw text to parse
     ///
     /// # Returns
-    /// * `Vec<TextualObjectTicket>` - list of TextualObjectTicket extracted from the text
-    pub fn scan_text_for_tickets(text: &str, opt: ToParserOption) -> Vec<ToTicket> {
-        let lines: &Vec<String> = &text.lines().map(|s| s.to_string()).collect();
-        let re = Regex::new(format!(r"{}(.*?){}", escape(&opt.to_marker.left_marker), escape(&opt.to_marker.right_marker)).as_str()).unwrap();
-        let mut result = Vec::new();
-        // iterate with line number
-        for (line_number, line) in lines.iter().enumerate() {
-            // iterate with match
-            for m in re.captures_iter(line) {
-                // get the match position
-                let position = ToTicketPositionInfo::from_match(&m, line_number);
-                // get first group of match
-                let content = m.get(1).unwrap().as_str();
-                // parse the match
-                let to_ticket = ToTicket::parse(content, &opt, Some(position));
-                // add the match to the result
-                result.push(to_ticket);
-            }
-        }
-
-        result
+    /// * `ToParseResult` - list of TextualObjectTicket extracted from the text, and the problems found in them.
+    ///
+    /// A malformed ticket is still returned as far as it can be read, and never stops the scan.
+    /// Compiles the patterns on each call, keep a `ToScanner` to scan several texts or to read tickets from a reader.
+    pub fn scan_text_for_tickets(text: &str, opt: ToParserOption) -> ToParseResult {
+        ToScanner::new(opt).scan_text(text)
     }
 }
 
@@ -44,12 +27,15 @@ impl ToParser {
 mod tests {
     use crate::to_parser::parser::ToParser;
     use crate::to_parser::parser_option::ToParserOption;
+    use crate::to_parser::scanner::ToScanner;
+    use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+    use crate::to_ticket::to_ticket_struct::ToTicket;
 
     #[test]
     fn test_one_mark() {
         let raw_text = "[[id:1]]";
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(raw_text, opt).tickets;
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].ticket_id, "1");
     }
@@ -59,7 +45,7 @@ mod tests {
     fn test_two_marks() {
         let raw_text = "[[id:1]][[id:2]]";
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(raw_text, opt).tickets;
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].ticket_id, "1");
         assert_eq!(result[1].ticket_id, "2");
@@ -70,7 +56,7 @@ mod tests {
     fn test_two_marks_different_positions() {
         let raw_text = "[[id:1]]\n[[id:2]]";
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(raw_text, opt).tickets;
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].ticket_id, "1");
         assert_eq!(result[1].ticket_id, "2");
@@ -81,7 +67,7 @@ mod tests {
     fn test_three_marks_different_positions() {
         let raw_text = "[[id:1]]\n[[id:2]]\n[[id:3]]";
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(raw_text, opt).tickets;
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].ticket_id, "1");
         assert_eq!(result[1].ticket_id, "2");
@@ -95,7 +81,7 @@ mod tests {
         let text = "[[id:1]]";
         let raw_text = format!("{}{}", indent, text);
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(&raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(&raw_text, opt).tickets;
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].ticket_id, "1");
         assert_eq!(result[0].to_intext_option.as_ref().unwrap().line, 0);
@@ -110,7 +96,7 @@ mod tests {
         let text = "[[id:1]]";
         let raw_text = format!("\n{}{}", indent, text);
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(&raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(&raw_text, opt).tickets;
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].ticket_id, "1");
         assert_eq!(result[0].to_intext_option.as_ref().unwrap().line, 1);
@@ -118,12 +104,131 @@ mod tests {
         assert_eq!(result[0].to_intext_option.as_ref().unwrap().length, text.len());
     }
 
+    // test escaped markers and separators inside values
+    #[test]
+    fn test_escaped_values_in_text() {
+        let mut ticket = ToTicket {
+            ticket_id: "abc".to_string(),
+            ..Default::default()
+        };
+        ticket.values.insert("title".to_string(), "Discipline: the Birth of the Prison | [[vol. 1]]\nsecond line".into());
+        let raw_text = format!("See {} and {}.", ticket.print(None), "[[id:2]]");
+        let result = ToParser::scan_text_for_tickets(&raw_text, ToParserOption::default()).tickets;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], ticket);
+        assert_eq!(result[1].ticket_id, "2");
+    }
+
+    // test a ticket wrapped over lines
+    #[test]
+    fn test_wrapped_ticket() {
+        let raw_text = "A long sentence [[id:abc | title: Discipline and\n  Punish]] and\nmore [[id:2]]";
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].ticket_id, "abc");
+        assert_eq!(result[0].values.get("title").unwrap(), "Discipline and Punish");
+        let position = result[0].to_intext_option.as_ref().unwrap();
+        assert_eq!(position.line, 0);
+        assert_eq!(position.column, "A long sentence ".len());
+        assert_eq!(position.end_line, 1);
+        assert_eq!(position.end_column, "  Punish]]".len());
+        assert_eq!(position.raw_text, "[[id:abc | title: Discipline and\n  Punish]]");
+        assert_eq!(result[1].ticket_id, "2");
+        assert_eq!(result[1].to_intext_option.as_ref().unwrap().line, 2);
+    }
+
+    // test the maximum span of a ticket
+    #[test]
+    fn test_max_lines_per_ticket() {
+        let raw_text = "[[id:abc\n1\n2\n3]] [[id:2]]";
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].ticket_id, "2");
+
+        let single_line = ToParserOption {
+            max_lines_per_ticket: 1,
+            ..Default::default()
+        };
+        let result = ToParser::scan_text_for_tickets("[[id:\nabc]]", single_line).tickets;
+        assert_eq!(result.len(), 0);
+    }
+
+    // test a malformed ticket does not stop the scan
+    #[test]
+    fn test_malformed_ticket_in_text() {
+        let raw_text = "[[id:1]]\nsome text [[id:2|updated:yesterday]]\n[[id:3]]";
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        assert_eq!(result.tickets.len(), 3);
+        assert_eq!(result.tickets[1].ticket_id, "2");
+        assert!(result.has_errors());
+        assert_eq!(result.diagnostics.len(), 1);
+        let position = result.diagnostics[0].position.as_ref().unwrap();
+        assert_eq!(position.line, 1);
+        assert_eq!(position.column, "some text ".len());
+    }
+
+    // test paired tickets enclosing a passage
+    #[test]
+    fn test_spans() {
+        let raw_text = "Intro [[id:a]]first [[id:b]]inner[[/id:b]]\npassage[[/id:a]] then [[id:c]] alone.";
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let ids: Vec<&str> = result.tickets.iter().map(|ticket| ticket.ticket_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert!(result.diagnostics.is_empty());
+        assert_eq!(result.spans.len(), 2);
+        let outer = &result.spans[0];
+        assert_eq!(outer.ticket_id, "a");
+        assert_eq!(outer.ticket_index, 0);
+        assert_eq!(outer.text, "first [[id:b]]inner[[/id:b]]\npassage");
+        assert_eq!(outer.depth, 0);
+        assert_eq!(outer.open.byte_offset, "Intro ".len());
+        assert_eq!(outer.close.raw_text, "[[/id:a]]");
+        assert_eq!(outer.close.line, 1);
+        assert_eq!(outer.close.end_column, "passage[[/id:a]]".len());
+        let inner = &result.spans[1];
+        assert_eq!((inner.ticket_id.as_str(), inner.ticket_index, inner.text.as_str(), inner.depth), ("b", 1, "inner", 1));
+
+        // the innermost ticket with the same id is closed first
+        let result = ToParser::scan_text_for_tickets("[[id:a]]1 [[id:a]]2[[/id:a]] 3[[/id:a]]", ToParserOption::default());
+        let spans: Vec<(&str, usize)> = result.spans.iter().map(|span| (span.text.as_str(), span.depth)).collect();
+        assert_eq!(spans, vec![("1 [[id:a]]2[[/id:a]] 3", 0), ("2", 1)]);
+    }
+
+    // test closing tickets that do not pair
+    #[test]
+    fn test_unbalanced_spans() {
+        let raw_text = "[[id:a]] x [[id:b]] y [[/id:a]] z [[/id:b]]\n{{id:c}} [[/id:c]]";
+        let opt = ToParserOption {
+            dialects: ToMarkerDialect::all(),
+            ..Default::default()
+        };
+        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        assert_eq!(result.tickets.len(), 3);
+        assert_eq!(result.spans.len(), 1);
+        assert_eq!(result.spans[0].text, " x [[id:b]] y ");
+        assert!(result.has_errors());
+        let messages: Vec<&str> = result.diagnostics.iter().map(|diagnostic| diagnostic.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "Span of ticket \"b\" overlaps the span of ticket \"a\", spans must nest",
+            // a ticket is only closed in its own dialect
+            "Closing ticket \"c\" has no opening ticket before it",
+        ]);
+        let position = result.diagnostics[1].position.as_ref().unwrap();
+        assert_eq!((position.line, position.column), (1, "{{id:c}} ".len()));
+
+        // the reader returns the tickets without their closing tickets
+        let scanned: Vec<String> = ToScanner::new(ToParserOption::default()).scan_reader(raw_text.as_bytes())
+            .map(|ticket| ticket.unwrap().0.ticket_id)
+            .collect();
+        assert_eq!(scanned, vec!["a", "b"]);
+    }
+
     // test tag scanning
     #[test]
     fn test_tag_scanning() {
         let raw_text = "[[IMPORTANT|RELEVANT|THIS is something that blahblah]]";
         let opt = ToParserOption::default();
-        let result = ToParser::scan_text_for_tickets(raw_text, opt);
+        let result = ToParser::scan_text_for_tickets(raw_text, opt).tickets;
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].values.len(), 3);
         let first_key = result[0].values.keys().next().unwrap();
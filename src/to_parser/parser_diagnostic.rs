@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use crate::to_parser::parser_span::ToTicketSpan;
+use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ToDiagnosticSeverity {
+    // the ticket could not be read as written, e.g. a malformed date
+    Error,
+    // the ticket was read but is probably not what the writer meant, e.g. an empty id
+    Warning,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToDiagnosticKind {
+    // a problem of no more specific kind, e.g. in diagnostics serialized before kinds were recorded
+    #[default]
+    Other,
+    EmptyId,
+    // a date that cannot be read with the date format
+    MalformedDate,
+    // a closing ticket without a ticket to close, e.g. [[/id:abc]] alone
+    UnopenedSpan,
+    // a span that ends after the span it starts in
+    OverlappingSpan,
+    // a ticket longer than max_ticket_length, which is not read
+    TicketTooLong,
+}
+
+/// problem found while parsing a ticket, with the span of the ticket in the text when it is known
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToParseDiagnostic {
+    #[serde(default)]
+    pub kind: ToDiagnosticKind,
+    pub message: String,
+    pub severity: ToDiagnosticSeverity,
+    pub position: Option<ToTicketPositionInfo>,
+}
+
+impl ToParseDiagnostic {
+    pub fn error(message: String, position: Option<ToTicketPositionInfo>) -> Self {
+        ToParseDiagnostic {
+            kind: ToDiagnosticKind::default(),
+            message,
+            severity: ToDiagnosticSeverity::Error,
+            position,
+        }
+    }
+
+    pub fn warning(message: String, position: Option<ToTicketPositionInfo>) -> Self {
+        ToParseDiagnostic {
+            kind: ToDiagnosticKind::default(),
+            message,
+            severity: ToDiagnosticSeverity::Warning,
+            position,
+        }
+    }
+
+    /// the same diagnostic of a given kind, e.g. `ToParseDiagnostic::error(message, position).with_kind(ToDiagnosticKind::MalformedDate)`
+    pub fn with_kind(mut self, kind: ToDiagnosticKind) -> Self {
+        self.kind = kind;
+        self
+    }
+}
+
+/// tickets found in a text, with the problems found while parsing them
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ToParseResult {
+    pub tickets: Vec<ToTicket>,
+    pub diagnostics: Vec<ToParseDiagnostic>,
+    // passages enclosed by pairs of tickets, in order of their start
+    #[serde(default)]
+    pub spans: Vec<ToTicketSpan>,
+}
+
+impl ToParseResult {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == ToDiagnosticSeverity::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_diagnostic_without_kind() {
+        let diagnostic: ToParseDiagnostic = serde_json::from_str(r#"{"message": "Ticket has an empty id", "severity": "Warning", "position": null}"#).unwrap();
+        assert_eq!(diagnostic, ToParseDiagnostic::warning("Ticket has an empty id".to_string(), None));
+        assert_eq!(diagnostic.kind, ToDiagnosticKind::Other);
+    }
+}
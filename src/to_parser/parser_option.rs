@@ -1,8 +1,20 @@
-use crate::to_ticket::to_ticket_marker::ToMarker;
+use crate::to_ticket::to_ticket_marker::{ToMarker, ToMarkerDialect};
+use crate::to_ticket::to_ticket_spec::ToTicketSpec;
 
+#[derive(Clone)]
 pub struct ToParserOption {
     pub to_marker: ToMarker,
     pub date_format: String,
+    // maximum number of lines a ticket may span, e.g. when an editor hard-wraps it; 1 only finds tickets within a line
+    pub max_lines_per_ticket: usize,
+    // maximum number of bytes of a ticket, markers included; a reader keeps at most this much of a ticket not yet closed
+    pub max_ticket_length: usize,
+    // ways of writing tickets to accept, e.g. [[...]] and 【【...】】; the markers of to_marker are used for DoubleSquare
+    pub dialects: Vec<ToMarkerDialect>,
+    // only read tickets in markdown prose, skipping front matter, code, HTML comments and wiki-links without an id
+    pub markdown: bool,
+    // keys of the reserved fields
+    pub ticket_spec: ToTicketSpec,
 }
 
 impl Default for ToParserOption {
@@ -10,6 +22,11 @@ impl Default for ToParserOption {
         ToParserOption {
             to_marker: ToMarker::default(),
             date_format: String::from("%Y-%m-%d %H:%M:%S"),
+            max_lines_per_ticket: 3,
+            max_ticket_length: 4096,
+            dialects: vec![ToMarkerDialect::DoubleSquare],
+            markdown: false,
+            ticket_spec: ToTicketSpec::default(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::to_parser::parser_diagnostic::{ToDiagnosticKind, ToParseDiagnostic};
+use crate::to_ticket::to_ticket_marker::ToMarkerDialect;
+use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+/// Passage of a text annotated by a pair of tickets, e.g. `[[id:abc]]the passage[[/id:abc]]`.
+/// The opening ticket is an ordinary ticket, the closing one only repeats its id.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ToTicketSpan {
+    pub ticket_id: String,
+    // index of the opening ticket in the tickets of the scan
+    pub ticket_index: usize,
+    // text between the two tickets, as written
+    pub text: String,
+    // the span starts at the opening ticket and ends after the closing one
+    pub open: ToTicketPositionInfo,
+    pub close: ToTicketPositionInfo,
+    // number of spans it is nested in, 0 when it is in no other span
+    pub depth: usize,
+}
+
+/// closing ticket of a span, e.g. `[[/id:abc]]`
+#[derive(Clone, Debug)]
+pub(crate) struct ToSpanClose {
+    pub ticket_id: String,
+    pub dialect: ToMarkerDialect,
+    pub position: ToTicketPositionInfo,
+}
+
+/// Pair the closing tickets with the tickets they close, in order of the text.
+/// A closing ticket closes the last ticket before it with the same id and dialect that is not closed yet;
+/// tickets left unclosed are single tickets. Spans must nest: the tickets a span encloses cannot open a span
+/// that ends after it, and such a closing ticket, like one without an opening ticket, is reported.
+pub(crate) fn match_spans(text: &str, tickets: &[ToTicket], closes: &[ToSpanClose]) -> (Vec<ToTicketSpan>, Vec<ToParseDiagnostic>) {
+    let mut spans = Vec::new();
+    let mut diagnostics = Vec::new();
+    // tickets that may still be closed, innermost last
+    let mut open: Vec<usize> = Vec::new();
+    // tickets inside a span that has been closed, with the id of that span
+    let mut enclosed: Vec<(usize, String)> = Vec::new();
+    let mut next_ticket = 0;
+    for close in closes {
+        while let Some(ticket) = tickets.get(next_ticket) {
+            match &ticket.to_intext_option {
+                Some(position) if position.byte_offset >= close.position.byte_offset => break,
+                Some(_) => open.push(next_ticket),
+                None => {}
+            }
+            next_ticket += 1;
+        }
+
+        let closes_ticket = |index: &usize| tickets[*index].ticket_id == close.ticket_id && tickets[*index].to_dialect == close.dialect;
+        match open.iter().rposition(closes_ticket) {
+            Some(stack_index) => {
+                let ticket_index = open[stack_index];
+                let opening = tickets[ticket_index].to_intext_option.clone().unwrap_or_default();
+                for inner in open.drain(stack_index + 1..) {
+                    enclosed.push((inner, close.ticket_id.clone()));
+                }
+                open.pop();
+                spans.push(ToTicketSpan {
+                    ticket_id: close.ticket_id.clone(),
+                    ticket_index,
+                    text: text.get(opening.end_byte_offset..close.position.byte_offset).unwrap_or_default().to_string(),
+                    open: opening,
+                    close: close.position.clone(),
+                    depth: 0,
+                });
+            }
+            None => match enclosed.iter().rposition(|(index, _)| closes_ticket(index)) {
+                Some(enclosed_index) => {
+                    let (_, outer_id) = enclosed.remove(enclosed_index);
+                    diagnostics.push(ToParseDiagnostic::error(
+                        format!("Span of ticket \"{}\" overlaps the span of ticket \"{}\", spans must nest", close.ticket_id, outer_id),
+                        Some(close.position.clone()),
+                    ).with_kind(ToDiagnosticKind::OverlappingSpan));
+                }
+                None => diagnostics.push(ToParseDiagnostic::error(
+                    format!("Closing ticket \"{}\" has no opening ticket before it", close.ticket_id),
+                    Some(close.position.clone()),
+                ).with_kind(ToDiagnosticKind::UnopenedSpan)),
+            },
+        }
+    }
+
+    spans.sort_by_key(|span| span.open.byte_offset);
+    let bounds: Vec<(usize, usize)> = spans.iter().map(|span| (span.open.byte_offset, span.close.end_byte_offset)).collect();
+    for span in spans.iter_mut() {
+        let (start, end) = (span.open.byte_offset, span.close.end_byte_offset);
+        span.depth = bounds.iter().filter(|(other_start, other_end)| *other_start < start && end <= *other_end).count();
+    }
+    (spans, diagnostics)
+}
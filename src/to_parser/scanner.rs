@@ -0,0 +1,732 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::BufRead;
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use regex::{escape, Regex};
+use tokio::io::AsyncBufRead;
+
+use crate::to_parser::markdown::ToMarkdownMask;
+use crate::to_parser::parser_diagnostic::{ToDiagnosticKind, ToParseDiagnostic, ToParseResult};
+use crate::to_parser::parser_option::ToParserOption;
+use crate::to_parser::parser_span::{match_spans, ToSpanClose};
+use crate::to_ticket::to_ticket_marker::{ToMarker, ToMarkerDialect};
+use crate::to_ticket::to_ticket_position::{line_starts, ToLineStarts, ToTicketPositionInfo};
+use crate::to_ticket::to_ticket_spec::ToTicketField;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+/// a ticket read from a text with the problems found in it
+pub type ToScannedTicket = (ToTicket, Vec<ToParseDiagnostic>);
+
+// most bytes appended to the scanned text per read, whatever the buffer of the reader
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Scanner holding the compiled patterns for a parser option, to be reused across texts and readers.
+#[derive(Clone)]
+pub struct ToScanner {
+    patterns: Vec<ToDialectPattern>,
+    line_break_regex: Regex,
+    max_lines: usize,
+    max_length: usize,
+    markdown: bool,
+}
+
+// compiled pattern of one dialect
+#[derive(Clone)]
+struct ToDialectPattern {
+    dialect: ToMarkerDialect,
+    // the parser option with the marker of the dialect
+    opt: ToParserOption,
+    ticket_regex: Regex,
+    // start of a ticket, kept in memory while its end may still come
+    left_marker: String,
+}
+
+// where the scanned text starts in the whole document, which may be inside a line
+#[derive(Clone, Copy, Default)]
+struct TextOrigin {
+    line: usize,
+    byte_offset: usize,
+    char_offset: usize,
+    // columns of the start of the text in its line
+    column: usize,
+    char_column: usize,
+    utf16_column: usize,
+}
+
+impl TextOrigin {
+    // the position in the whole document of a position in the scanned text
+    fn shift(&self, mut position: ToTicketPositionInfo) -> ToTicketPositionInfo {
+        if position.line == 0 {
+            position.column += self.column;
+            position.char_column += self.char_column;
+            position.utf16_column += self.utf16_column;
+        }
+        if position.end_line == 0 {
+            position.end_column += self.column;
+            position.end_char_column += self.char_column;
+            position.end_utf16_column += self.utf16_column;
+        }
+        position.shifted(self.line, self.byte_offset, self.char_offset)
+    }
+
+    // move the origin forward over the start of the text, up to `until` in its line `line`
+    fn advance(&mut self, text: &str, line_starts: &ToLineStarts, line: usize, until: usize) {
+        let line_start = line_starts.bytes[line];
+        let skipped = &text[line_start..until];
+        if line > 0 {
+            self.column = 0;
+            self.char_column = 0;
+            self.utf16_column = 0;
+        }
+        let skipped_chars = skipped.chars().count();
+        self.line += line;
+        self.byte_offset += until;
+        self.char_offset += line_starts.chars[line] + skipped_chars;
+        self.column += skipped.len();
+        self.char_column += skipped_chars;
+        self.utf16_column += skipped.encode_utf16().count();
+    }
+}
+
+// a text to scan, the whole document or its end read so far
+#[derive(Clone, Copy)]
+struct ScanInput<'a> {
+    text: &'a str,
+    // no more lines will come
+    at_end: bool,
+    origin: TextOrigin,
+    line_starts: &'a ToLineStarts,
+    // byte ranges of the whole document in which tickets are skipped
+    excluded: &'a [Range<usize>],
+}
+
+impl ToScanner {
+    pub fn new(opt: ToParserOption) -> Self {
+        let escape_character = escape(&opt.to_marker.escape_character.to_string());
+        let mut dialects: Vec<ToMarkerDialect> = Vec::new();
+        for dialect in opt.dialects.iter() {
+            if !dialects.contains(dialect) {
+                dialects.push(*dialect);
+            }
+        }
+        if dialects.is_empty() {
+            dialects.push(ToMarkerDialect::default());
+        }
+        let patterns = dialects.into_iter().map(|dialect| {
+            match dialect.marker(&opt.to_marker) {
+                Some(marker) => ToDialectPattern {
+                    dialect,
+                    // content is any run of escaped characters or characters other than the escape character, so that escaped right markers do not end a ticket.
+                    // Content may contain line breaks, the span is checked against max_lines_per_ticket
+                    ticket_regex: Regex::new(format!(r"(?s){}((?:{}.|[^{}])*?){}",
+                                                     escape(&marker.left_marker),
+                                                     escape_character,
+                                                     escape_character,
+                                                     escape(&marker.right_marker)).as_str()).unwrap(),
+                    left_marker: marker.left_marker.clone(),
+                    opt: ToParserOption {
+                        to_marker: marker,
+                        ..opt.clone()
+                    },
+                },
+                None => ToDialectPattern {
+                    dialect,
+                    // [label](to:id), the label may be empty and wrapped, the id is a single word
+                    ticket_regex: Regex::new(format!(r"(?s)\[((?:{0}.|[^{0}\]])*)\]\(to:((?:{0}.|[^{0}\)\s])+)\)", escape_character).as_str()).unwrap(),
+                    left_marker: "[".to_string(),
+                    opt: opt.clone(),
+                },
+            }
+        }).collect();
+        // a line break with the indentation around it, as left by hard-wrapping editors
+        let line_break_regex = Regex::new(r"[ \t]*\r?\n[ \t]*").unwrap();
+        ToScanner {
+            patterns,
+            line_break_regex,
+            max_lines: opt.max_lines_per_ticket.max(1),
+            max_length: opt.max_ticket_length,
+            markdown: opt.markdown,
+        }
+    }
+
+    /// Parse all tickets in a text, and the spans enclosed by pairs of tickets, e.g. `[[id:abc]]a passage[[/id:abc]]`.
+    /// A malformed ticket is still returned as far as it can be read, and never stops the scan.
+    pub fn scan_text(&self, text: &str) -> ToParseResult {
+        let mut tickets = Vec::new();
+        let mut closes = Vec::new();
+        let excluded = if self.markdown { ToMarkdownMask::excluded_ranges(text) } else { Vec::new() };
+        let input = ScanInput {
+            text,
+            at_end: true,
+            origin: TextOrigin::default(),
+            line_starts: &line_starts(text),
+            excluded: &excluded,
+        };
+        let mut too_long = Vec::new();
+        self.scan_from(input, &mut Vec::new(), &mut tickets, &mut closes, &mut too_long);
+        let mut result = ToParseResult::default();
+        for (ticket, diagnostics) in tickets {
+            result.tickets.push(ticket);
+            result.diagnostics.extend(diagnostics);
+        }
+        result.diagnostics.extend(too_long);
+        let (spans, span_diagnostics) = match_spans(text, &result.tickets, &closes);
+        result.spans = spans;
+        result.diagnostics.extend(span_diagnostics);
+        result
+    }
+
+    /// Read tickets from a reader in chunks of bounded size, as they are found.
+    /// Only the text a ticket may still span is kept in memory, at most `max_lines_per_ticket` lines and `max_ticket_length` bytes of it
+    /// (whole lines for markdown-aware scans), so spans are not matched; their closing tickets are skipped.
+    /// A marker not closed within `max_ticket_length` bytes is dropped with a diagnostic, see `ToTicketReader::diagnostics`.
+    pub fn scan_reader<R: BufRead>(&self, reader: R) -> ToTicketReader<R> {
+        ToTicketReader {
+            scanner: self.clone(),
+            reader,
+            window: ToTicketWindow::default(),
+            done: false,
+        }
+    }
+
+    /// same as `scan_reader` for an async reader, as a stream of tickets
+    pub fn scan_async_reader<R: AsyncBufRead + Unpin>(&self, reader: R) -> ToAsyncTicketReader<R> {
+        ToAsyncTicketReader {
+            scanner: self.clone(),
+            reader,
+            window: ToTicketWindow::default(),
+            done: false,
+        }
+    }
+
+    // read the tickets closed in the text after the cursor of each dialect, in order of their start,
+    // and move the cursors to where to resume once more lines are appended.
+    // At the end of the input the cursors move to the end of the text; tickets in excluded ranges are skipped,
+    // and tickets longer than max_length are reported in diagnostics
+    fn scan_from(&self, input: ScanInput, cursors: &mut Vec<usize>, tickets: &mut Vec<ToScannedTicket>, closes: &mut Vec<ToSpanClose>, diagnostics: &mut Vec<ToParseDiagnostic>) {
+        cursors.resize(self.patterns.len(), 0);
+        for (pattern, cursor) in self.patterns.iter().zip(cursors.iter_mut()) {
+            *cursor = self.scan_pattern_from(pattern, input, *cursor, tickets, closes, diagnostics);
+        }
+        tickets.sort_by_key(|(ticket, _)| ticket.to_intext_option.as_ref().map(|position| position.byte_offset));
+        closes.sort_by_key(|close| close.position.byte_offset);
+    }
+
+    // read the tickets of one dialect closed in the text after `cursor`, and return the cursor to resume from
+    fn scan_pattern_from(&self, pattern: &ToDialectPattern, input: ScanInput, mut cursor: usize, tickets: &mut Vec<ToScannedTicket>, closes: &mut Vec<ToSpanClose>, diagnostics: &mut Vec<ToParseDiagnostic>) -> usize {
+        let ScanInput { text, at_end, origin, line_starts, excluded } = input;
+        let left_marker = pattern.left_marker.as_str();
+        while let Some(m) = pattern.ticket_regex.captures(&text[cursor..]) {
+            let whole_match = m.get(0).unwrap();
+            let start = cursor + whole_match.start();
+            let end = cursor + whole_match.end();
+            // get the match position
+            let position = ToTicketPositionInfo::from_span(text, start, end, line_starts);
+            if position.end_line - position.line + 1 > self.max_lines {
+                // too long to be a wrapped ticket, e.g. an unclosed marker; look for a ticket starting after this marker
+                cursor = start + left_marker.len();
+                continue;
+            }
+            let (document_start, document_end) = (origin.byte_offset + start, origin.byte_offset + end);
+            if excluded.iter().any(|range| range.start < document_end && document_start < range.end) {
+                // in code or a comment; a ticket may still start after this marker, e.g. when the marker is in inline code
+                cursor = start + left_marker.len();
+                continue;
+            }
+            if end - start > self.max_length {
+                diagnostics.push(self.too_long(pattern, input, start));
+                cursor = start + left_marker.len();
+                continue;
+            }
+            let position = origin.shift(position);
+            // get first group of match, unwrapped into a single line
+            let content = self.line_break_regex.replace_all(m.get(1).unwrap().as_str(), " ");
+            if pattern.dialect != ToMarkerDialect::MarkdownLink {
+                if let Some(ticket_id) = closed_id(&content, &pattern.opt) {
+                    closes.push(ToSpanClose {
+                        ticket_id,
+                        dialect: pattern.dialect,
+                        position,
+                    });
+                    cursor = end;
+                    continue;
+                }
+            }
+            if self.markdown && pattern.dialect != ToMarkerDialect::MarkdownLink && !has_id_entry(&content, &pattern.opt.to_marker) {
+                // a wiki-link to a note, e.g. [[Some note]], rather than a ticket
+                cursor = end;
+                continue;
+            }
+            let (mut ticket, diagnostics) = match pattern.dialect {
+                ToMarkerDialect::MarkdownLink => {
+                    let label = pattern.opt.to_marker.unescape(content.trim());
+                    let ticket = ToTicket {
+                        ticket_id: pattern.opt.to_marker.unescape(m.get(2).unwrap().as_str()),
+                        to_label: Some(label).filter(|label| !label.is_empty()),
+                        to_intext_option: Some(position),
+                        ..Default::default()
+                    };
+                    (ticket, Vec::new())
+                }
+                _ => ToTicket::parse(&content, &pattern.opt, Some(position)),
+            };
+            ticket.to_dialect = pattern.dialect;
+            tickets.push((ticket, diagnostics));
+            cursor = end;
+        }
+        if at_end {
+            return text.len();
+        }
+        // no ticket is closed after the cursor; resume from the first marker that text to come may still close
+        let mut search_start = cursor;
+        while let Some(open) = text[search_start..].find(left_marker) {
+            let open = search_start + open;
+            if text[open..].matches('\n').count() < self.max_lines {
+                if text.len() - open <= self.max_length {
+                    return open;
+                }
+                // the ticket would be too long once closed; this bounds the text kept while reading.
+                // A `[` is mostly prose rather than the start of a markdown link, so it is dropped without diagnostic
+                let document_open = origin.byte_offset + open;
+                if pattern.dialect != ToMarkerDialect::MarkdownLink && !excluded.iter().any(|range| range.contains(&document_open)) {
+                    diagnostics.push(self.too_long(pattern, input, open));
+                }
+            }
+            search_start = open + left_marker.len();
+        }
+        // or from the start of a marker cut at the end of the text
+        let cut_marker = left_marker.char_indices().skip(1)
+            .map(|(index, _)| &left_marker[..index])
+            .filter(|prefix| text[search_start..].ends_with(prefix))
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        text.len() - cut_marker
+    }
+
+    // diagnostic of a ticket longer than max_length, at its left marker
+    fn too_long(&self, pattern: &ToDialectPattern, input: ScanInput, start: usize) -> ToParseDiagnostic {
+        let position = ToTicketPositionInfo::from_span(input.text, start, start + pattern.left_marker.len(), input.line_starts);
+        ToParseDiagnostic::warning(
+            format!("\"{}\" is not closed within {} bytes, the ticket is not read", pattern.left_marker, self.max_length),
+            Some(input.origin.shift(position)),
+        ).with_kind(ToDiagnosticKind::TicketTooLong)
+    }
+}
+
+// whether the content of a ticket has an id entry
+fn has_id_entry(content: &str, marker: &ToMarker) -> bool {
+    marker.split_unescaped(content, &marker.value_entry_separator, usize::MAX).into_iter().any(|entry| {
+        let key = marker.split_unescaped(entry, &marker.value_separator, 2)[0];
+        marker.unescape(key.trim()) == "id"
+    })
+}
+
+// id of a closing ticket, e.g. `abc` for `[[/id:abc]]`; None for other tickets.
+// Entries after the id are ignored, the closing ticket only marks where the span ends
+fn closed_id(content: &str, opt: &ToParserOption) -> Option<String> {
+    let marker = &opt.to_marker;
+    let content = content.trim_start().strip_prefix('/')?;
+    let first_entry = marker.split_unescaped(content, &marker.value_entry_separator, 2)[0];
+    let mut pieces = marker.split_unescaped(first_entry, &marker.value_separator, 2).into_iter();
+    if marker.unescape(pieces.next().unwrap_or("").trim()) != opt.ticket_spec.key(ToTicketField::Id) {
+        return None;
+    }
+    let ticket_id = marker.unescape(pieces.next().unwrap_or("").trim());
+    if ticket_id.is_empty() { None } else { Some(ticket_id) }
+}
+
+// the end of a text being read: the text from the first ticket that may still be closed, and tickets found but not yet returned
+#[derive(Default)]
+struct ToTicketWindow {
+    text: String,
+    line_starts: ToLineStarts,
+    // where each dialect resumes
+    cursors: Vec<usize>,
+    origin: TextOrigin,
+    // tickets to return, in order of their start
+    tickets: VecDeque<ToScannedTicket>,
+    // tickets found after the cursor of another dialect, which may still find a ticket starting before them
+    pending: Vec<ToScannedTicket>,
+    // parts of the document that are not prose, for markdown-aware scans
+    markdown: ToMarkdownMask,
+    excluded: Vec<Range<usize>>,
+    // end of the text scanned, which for markdown-aware scans is the end of the lines given to the mask
+    scanned_end: usize,
+    // bytes of a char cut at the end of the last read
+    incomplete: Vec<u8>,
+    // tickets too long to be read
+    diagnostics: Vec<ToParseDiagnostic>,
+}
+
+impl ToTicketWindow {
+    // append bytes read, and read the tickets they close
+    fn push_bytes(&mut self, scanner: &ToScanner, bytes: &[u8], at_end: bool) -> io::Result<()> {
+        self.incomplete.extend_from_slice(bytes);
+        let bytes = std::mem::take(&mut self.incomplete);
+        let valid_up_to = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            // a char cut by the read, its end comes with the next one
+            Err(e) if e.error_len().is_none() && !at_end => e.valid_up_to(),
+            Err(_) => return Err(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")),
+        };
+        // checked above
+        let text = std::str::from_utf8(&bytes[..valid_up_to]).unwrap();
+        self.incomplete.extend_from_slice(&bytes[valid_up_to..]);
+        self.push_str(scanner, text, at_end);
+        Ok(())
+    }
+
+    // append text, and read the tickets it closes
+    fn push_str(&mut self, scanner: &ToScanner, appended: &str, at_end: bool) {
+        self.text.push_str(appended);
+        self.line_starts.push_str(appended);
+        if scanner.markdown {
+            // the mask reads whole lines, so the end of a line being read waits for its line break
+            let lines_end = if at_end { self.text.len() } else { *self.line_starts.bytes.last().unwrap() };
+            for line in self.text[self.scanned_end..lines_end].split_inclusive('\n') {
+                let line_start = self.origin.byte_offset + self.scanned_end;
+                self.excluded.extend(self.markdown.push_line(line, line_start));
+                self.scanned_end += line.len();
+            }
+        } else {
+            self.scanned_end = self.text.len();
+        }
+        let scanned_end = self.scanned_end;
+        let mut tickets = Vec::new();
+        let input = ScanInput {
+            text: &self.text[..scanned_end],
+            at_end,
+            origin: self.origin,
+            line_starts: &self.line_starts,
+            excluded: &self.excluded,
+        };
+        // spans may enclose more text than is kept, their closing tickets are dropped
+        scanner.scan_from(input, &mut self.cursors, &mut tickets, &mut Vec::new(), &mut self.diagnostics);
+        // tickets starting before every cursor are in order, no ticket to come starts before them
+        let keep_from = self.cursors.iter().copied().min().unwrap_or(scanned_end);
+        let keep_offset = self.origin.byte_offset + keep_from;
+        self.pending.extend(tickets);
+        self.pending.sort_by_key(|(ticket, _)| ticket.to_intext_option.as_ref().map(|position| position.byte_offset));
+        let ready = self.pending.partition_point(|(ticket, _)| ticket.to_intext_option.as_ref().map(|position| position.byte_offset) < Some(keep_offset));
+        self.tickets.extend(self.pending.drain(..ready));
+        // forget the text before the cursors, its tickets have been read
+        let (keep_line, _) = self.line_starts.line_and_column(keep_from);
+        let char_offset = self.origin.char_offset;
+        self.origin.advance(&self.text, &self.line_starts, keep_line, keep_from);
+        self.line_starts.drain(keep_from, self.origin.char_offset - char_offset);
+        self.text.drain(..keep_from);
+        for cursor in self.cursors.iter_mut() {
+            *cursor -= keep_from;
+        }
+        self.scanned_end -= keep_from;
+        let byte_offset = self.origin.byte_offset;
+        self.excluded.retain(|range| range.end > byte_offset);
+    }
+
+    // append at most a chunk of the buffer of a reader, an empty buffer being the end of the reader,
+    // and return how many bytes were used
+    fn push_buffer(&mut self, scanner: &ToScanner, buffer: &[u8]) -> io::Result<usize> {
+        let chunk = &buffer[..buffer.len().min(CHUNK_SIZE)];
+        self.push_bytes(scanner, chunk, chunk.is_empty())?;
+        Ok(chunk.len())
+    }
+}
+
+/// Iterator over the tickets of a reader, see `ToScanner::scan_reader`
+pub struct ToTicketReader<R: BufRead> {
+    scanner: ToScanner,
+    reader: R,
+    window: ToTicketWindow,
+    done: bool,
+}
+
+impl<R: BufRead> ToTicketReader<R> {
+    /// tickets not read because they are longer than `max_ticket_length`, found so far
+    pub fn diagnostics(&self) -> &[ToParseDiagnostic] {
+        &self.window.diagnostics
+    }
+}
+
+impl<R: BufRead> Iterator for ToTicketReader<R> {
+    type Item = io::Result<ToScannedTicket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ticket) = self.window.tickets.pop_front() {
+                return Some(Ok(ticket));
+            }
+            if self.done {
+                return None;
+            }
+            let read = match self.reader.fill_buf() {
+                Ok(buffer) => {
+                    self.done = buffer.is_empty();
+                    self.window.push_buffer(&self.scanner, buffer)
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            match read {
+                Ok(used) => self.reader.consume(used),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Stream of the tickets of an async reader, see `ToScanner::scan_async_reader`
+pub struct ToAsyncTicketReader<R: AsyncBufRead + Unpin> {
+    scanner: ToScanner,
+    reader: R,
+    window: ToTicketWindow,
+    done: bool,
+}
+
+impl<R: AsyncBufRead + Unpin> ToAsyncTicketReader<R> {
+    /// same as `ToTicketReader::diagnostics`
+    pub fn diagnostics(&self) -> &[ToParseDiagnostic] {
+        &self.window.diagnostics
+    }
+}
+
+impl<R: AsyncBufRead + Unpin> Stream for ToAsyncTicketReader<R> {
+    type Item = io::Result<ToScannedTicket>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(ticket) = this.window.tickets.pop_front() {
+                return Poll::Ready(Some(Ok(ticket)));
+            }
+            if this.done {
+                return Poll::Ready(None);
+            }
+            let read = match Pin::new(&mut this.reader).poll_fill_buf(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Ok(buffer)) => {
+                    this.done = buffer.is_empty();
+                    this.window.push_buffer(&this.scanner, buffer)
+                }
+                Poll::Ready(Err(e)) => Err(e),
+            };
+            match read {
+                Ok(used) => Pin::new(&mut this.reader).consume(used),
+                Err(e) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(e)));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use futures::StreamExt;
+
+    use super::*;
+
+    const TEXT: &str = "Intro [[id:1]] and [[id:2 | title: wrapped\n  over two lines]]\n\
+        an unclosed [[ marker\nthen\nmore\nlines [[id:3]]\n\
+        引文 é😀 [[id:4|title:a \\]\\] b]] [[id:5|updated:yesterday]]";
+
+    fn assert_same_as_scan_text(scanned: Vec<ToScannedTicket>, scanner: &ToScanner, text: &str) {
+        let expected = scanner.scan_text(text);
+        assert_eq!(scanned.len(), expected.tickets.len());
+        for ((ticket, _), expected_ticket) in scanned.iter().zip(expected.tickets.iter()) {
+            // tickets without an updated date are dated when read, so compare the rest
+            let mut ticket = ticket.clone();
+            ticket.to_updated = expected_ticket.to_updated;
+            assert_eq!(serde_json::to_value(&ticket).unwrap(), serde_json::to_value(expected_ticket).unwrap());
+        }
+        let diagnostics: Vec<ToParseDiagnostic> = scanned.into_iter().flat_map(|(_, diagnostics)| diagnostics).collect();
+        assert_eq!(diagnostics, expected.diagnostics);
+    }
+
+    #[test]
+    fn test_scan_text() {
+        let result = ToScanner::new(ToParserOption::default()).scan_text(TEXT);
+        let ids: Vec<&str> = result.tickets.iter().map(|ticket| ticket.ticket_id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4", "5"]);
+        assert_eq!(result.tickets[3].values.get("title").unwrap(), "a ]] b");
+        assert_eq!(result.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_scan_dialects() {
+        let text = "See [Discipline and\n Punish](to:abc), {{id:2}} and 【【id:3|p:5】】\n［［id:4］］ [[id:5]] [not a ticket](https://x)";
+        let scanner = ToScanner::new(ToParserOption {
+            dialects: ToMarkerDialect::all(),
+            ..Default::default()
+        });
+        let result = scanner.scan_text(text);
+        let ids: Vec<(&str, ToMarkerDialect)> = result.tickets.iter().map(|ticket| (ticket.ticket_id.as_str(), ticket.to_dialect)).collect();
+        assert_eq!(ids, vec![
+            ("abc", ToMarkerDialect::MarkdownLink),
+            ("2", ToMarkerDialect::DoubleCurly),
+            ("3", ToMarkerDialect::FullWidthLenticular),
+            ("4", ToMarkerDialect::FullWidthSquare),
+            ("5", ToMarkerDialect::DoubleSquare),
+        ]);
+        assert_eq!(result.tickets[0].to_label.as_deref(), Some("Discipline and Punish"));
+        assert_eq!(result.tickets[2].values.get("p").unwrap().to_string(), "5");
+        assert_eq!(result.tickets[2].to_intext_option.as_ref().unwrap().raw_text, "【【id:3|p:5】】");
+
+        // the reader finds the same tickets
+        let scanned: Vec<ToScannedTicket> = scanner.scan_reader(BufReader::with_capacity(2, text.as_bytes())).map(|ticket| ticket.unwrap()).collect();
+        let scanned_ids: Vec<(String, ToMarkerDialect)> = scanned.into_iter().map(|(ticket, _)| (ticket.ticket_id, ticket.to_dialect)).collect();
+        assert_eq!(scanned_ids, ids.into_iter().map(|(id, dialect)| (id.to_string(), dialect)).collect::<Vec<_>>());
+
+        // only the default dialect unless asked otherwise
+        let result = ToScanner::new(ToParserOption::default()).scan_text(text);
+        assert_eq!(result.tickets.len(), 1);
+        assert_eq!(result.tickets[0].ticket_id, "5");
+    }
+
+    #[test]
+    fn test_scan_markdown() {
+        let text = "---\ncites: [[id:front]]\n---\n# Notes [[id:1]]\n\n```\n[[id:code]]\n```\n\
+            See `[[id:inline]]` and `[[`[[id:2]], <!-- [[id:comment]]\n[[id:still_comment]] --> [[Some note]] [[id:3|\n  p: 4]]";
+        let markdown = ToParserOption {
+            markdown: true,
+            ..Default::default()
+        };
+        let scanner = ToScanner::new(markdown);
+        let result = scanner.scan_text(text);
+        let ids: Vec<&str> = result.tickets.iter().map(|ticket| ticket.ticket_id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+        let position = result.tickets[1].to_intext_option.as_ref().unwrap();
+        assert_eq!(position.line, 8);
+        assert_eq!(position.column, "See `[[id:inline]]` and `[[`".len());
+        assert_eq!(position.byte_offset, text.find("[[id:2]]").unwrap());
+
+        // the reader skips the same parts
+        let scanned: Vec<String> = scanner.scan_reader(BufReader::with_capacity(3, text.as_bytes()))
+            .map(|ticket| ticket.unwrap().0.ticket_id)
+            .collect();
+        assert_eq!(scanned, ids);
+
+        // every ticket is read otherwise
+        let result = ToScanner::new(ToParserOption::default()).scan_text(text);
+        assert_eq!(result.tickets.len(), 9);
+    }
+
+    // a buffer smaller than a ticket splits tickets across reads
+    #[test]
+    fn test_scan_reader_with_small_buffer() {
+        let scanner = ToScanner::new(ToParserOption::default());
+        for capacity in [1, 3, 7, 64] {
+            let reader = BufReader::with_capacity(capacity, TEXT.as_bytes());
+            let scanned: Vec<ToScannedTicket> = scanner.scan_reader(reader).map(|ticket| ticket.unwrap()).collect();
+            assert_same_as_scan_text(scanned, &scanner, TEXT);
+        }
+    }
+
+    // a ticket closed after a ticket of another dialect that starts later is still returned first
+    #[test]
+    fn test_scan_reader_keeps_order_across_reads() {
+        let text = "[[id:1 | title: see {{id:2}}\n and more]] {{id:3}} [then](to:4)";
+        let scanner = ToScanner::new(ToParserOption {
+            dialects: ToMarkerDialect::all(),
+            ..Default::default()
+        });
+        for capacity in [1, 5, 64] {
+            let reader = BufReader::with_capacity(capacity, text.as_bytes());
+            let scanned: Vec<ToScannedTicket> = scanner.scan_reader(reader).map(|ticket| ticket.unwrap()).collect();
+            assert_same_as_scan_text(scanned, &scanner, text);
+        }
+        let ids: Vec<String> = scanner.scan_text(text).tickets.into_iter().map(|ticket| ticket.ticket_id).collect();
+        assert_eq!(ids, vec!["1", "2", "3", "4"]);
+    }
+
+    // only the text of the unclosed ticket is kept
+    #[test]
+    fn test_scan_reader_window_is_bounded() {
+        let scanner = ToScanner::new(ToParserOption::default());
+        let mut window = ToTicketWindow::default();
+        for line in TEXT.split_inclusive('\n') {
+            window.push_str(&scanner, line, false);
+            assert!(window.text.matches('\n').count() <= 3);
+        }
+        // a long line without line breaks is not kept either
+        let long_text = "no ticket here ".repeat(1000);
+        for chunk in long_text.as_bytes().chunks(100) {
+            window.push_bytes(&scanner, chunk, false).unwrap();
+            assert!(window.text.len() <= 100);
+        }
+        assert_eq!(window.origin.byte_offset + window.text.len(), TEXT.len() + long_text.len());
+        assert_eq!(window.line_starts, line_starts(&window.text));
+
+        // nor a marker cut by a read, but its start
+        window.push_str(&scanner, "a cut [", false);
+        assert_eq!(window.text, "[");
+        window.push_str(&scanner, "[id:6]] on a long line", true);
+        let (ticket, _) = window.tickets.pop_back().unwrap();
+        let position = ticket.to_intext_option.unwrap();
+        assert_eq!(position.line, TEXT.matches('\n').count());
+        assert_eq!(position.byte_offset, TEXT.len() + long_text.len() + "a cut ".len());
+        assert_eq!(position.column, position.byte_offset - TEXT.rfind('\n').unwrap() - 1);
+    }
+
+    #[test]
+    fn test_scan_too_long_ticket() {
+        let text = format!("[[id:1 | note: {}]] [[id:2]]", "a".repeat(5000));
+        let scanner = ToScanner::new(ToParserOption::default());
+        let result = scanner.scan_text(&text);
+        assert_eq!(result.tickets.len(), 1);
+        assert_eq!(result.tickets[0].ticket_id, "2");
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].kind, ToDiagnosticKind::TicketTooLong);
+        assert_eq!(result.diagnostics[0].position.as_ref().unwrap().raw_text, "[[");
+
+        // the reader drops the ticket once it is too long to be read
+        let mut reader = scanner.scan_reader(BufReader::with_capacity(100, text.as_bytes()));
+        let ids: Vec<String> = reader.by_ref().map(|ticket| ticket.unwrap().0.ticket_id).collect();
+        assert_eq!(ids, vec!["2"]);
+        assert_eq!(reader.diagnostics(), result.diagnostics.as_slice());
+        let mut window = ToTicketWindow::default();
+        for chunk in text.as_bytes().chunks(100) {
+            window.push_bytes(&scanner, chunk, false).unwrap();
+            assert!(window.text.len() <= 4096 + 100);
+        }
+
+        // longer tickets are read when allowed
+        let scanner = ToScanner::new(ToParserOption {
+            max_ticket_length: 8192,
+            ..Default::default()
+        });
+        assert_eq!(scanner.scan_text(&text).tickets.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_reader_error() {
+        let scanner = ToScanner::new(ToParserOption::default());
+        let invalid_utf8: &[u8] = &[b'[', b'[', 0xff, b']', b']', b'\n'];
+        let mut reader = scanner.scan_reader(invalid_utf8);
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        // a char cut at the end of the reader
+        let mut reader = scanner.scan_reader("[[id:1]] 引".as_bytes().split_last().unwrap().1);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scan_async_reader() {
+        let scanner = ToScanner::new(ToParserOption::default());
+        let reader = tokio::io::BufReader::with_capacity(5, TEXT.as_bytes());
+        let scanned: Vec<ToScannedTicket> = scanner.scan_async_reader(reader).map(|ticket| ticket.unwrap()).collect().await;
+        assert_same_as_scan_text(scanned, &scanner, TEXT);
+    }
+}
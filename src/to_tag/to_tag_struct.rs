@@ -1,6 +1,5 @@
 
 
-use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use crate::to_parser::parser::ToParser;
 
@@ -11,16 +10,16 @@ use crate::to_ticket::to_ticket_struct::ToTicket;
 
 #[derive(Deserialize, Serialize, Clone)]
 pub struct ToTag {
-    key: String,
-    value: Option<String>,
-    note: Option<String>,
+    pub(crate) key: String,
+    pub(crate) value: Option<String>,
+    pub(crate) note: Option<String>,
 }
 
 // implement from TextualObjectTicket
 
 impl From<ToTicket> for ToTag {
     fn from(to_ticket: ToTicket) -> ToTag {
-        let values: IndexMap<String, String> = to_ticket.values;
+        let values = to_ticket.values;
         let mut key = String::new();
         if values.len() >= 1 {
             key = values.keys().nth(0).unwrap().to_string();
@@ -56,7 +55,7 @@ impl ToTag {
     // Cleaned text = text without tags.
     // List of tags = list of tags found in text.
     pub fn scan_text_for_tags(text: &str) -> (String, Vec<ToTag>) {
-        let all_tickets = ToParser::scan_text_for_tickets(text, ToParserOption::default());
+        let all_tickets = ToParser::scan_text_for_tickets(text, ToParserOption::default()).tickets;
         let mut tags: Vec<ToTag> = Vec::new();
         for ticket in all_tickets {
             let tag = ToTag::from(ticket);
@@ -72,7 +71,7 @@ impl ToTag {
 
     }
 
-    // implement print tag
+    // implement print tag; to render it with the templates of a project, see `ToTemplateSet::render_tag`
     pub fn print_tag(&self, to_mark: Option<ToMarker>) -> String {
         // create default to_marker if not provided
         let mut to_marker = ToMarker::default();
@@ -109,7 +108,7 @@ mod test {
     #[test]
     fn test_from_ticket_to_tag() {
         let raw_text = "[[KEY|VALUE|NOTE]]";
-        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
         let first_ticket = result[0].clone();
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].values.get("KEY").unwrap(), "");
@@ -123,7 +122,7 @@ mod test {
     #[test]
     fn test_noise_ticket_to_tag() {
         let raw_text = "[[KEY:value|VALUE:2|:3]]";
-        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
         let first_ticket = result[0].clone();
         assert_eq!(result.len(), 1);
         let tag = ToTag::from(first_ticket);
@@ -138,7 +137,7 @@ mod test {
     #[test]
     fn test_ticket_to_tag_key_value_only() {
         let raw_text = "[[KEY:value|VALUE:2]]";
-        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
         let first_ticket = result[0].clone();
         assert_eq!(result.len(), 1);
         let tag = ToTag::from(first_ticket);
@@ -152,7 +151,7 @@ mod test {
     #[test]
     fn test_ticket_to_tag_key_only() {
         let raw_text = "[[KEY:value]]";
-        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
         let first_ticket = result[0].clone();
         assert_eq!(result.len(), 1);
         let tag = ToTag::from(first_ticket);
@@ -165,7 +164,7 @@ mod test {
     #[test]
     fn test_scan_text_for_tags() {
         let raw_text = "1[[KEY|VALUE|NOTE]]\n2[[KEY2|VALUE2|NOTE2]]\n3[[KEY3|VALUE3|NOTE3]]";
-        let _result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default());
+        let _result = ToParser::scan_text_for_tickets(raw_text, ToParserOption::default()).tickets;
         let (cleaned, tags) = ToTag::scan_text_for_tags(&raw_text);
         assert_eq!(cleaned, "1\n2\n3");
         assert_eq!(tags.len(), 3);
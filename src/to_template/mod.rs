@@ -0,0 +1,4 @@
+pub mod to_template_format;
+pub mod to_template_struct;
+pub mod to_template_context;
+pub mod to_template_set;
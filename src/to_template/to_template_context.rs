@@ -0,0 +1,72 @@
+use serde_json::{json, Value};
+
+use crate::to::to_struct::TextualObject;
+use crate::to_tag::to_tag_struct::ToTag;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+// dates in templates are written as in tickets
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Context of ticket and label templates:
+/// `id`, `values`, `label`, `updated`, `created`, `store_url`, `store_info`,
+/// and the ticket as printed: `printed`, `minimal` and `labelled`
+pub fn ticket_context(ticket: &ToTicket) -> Value {
+    let values: serde_json::Map<String, Value> = ticket.values.iter()
+        .map(|(key, value)| (key.clone(), value.to_json()))
+        .collect();
+    json!({
+        "id": ticket.ticket_id,
+        "values": values,
+        "label": ticket.to_label.clone().unwrap_or_default(),
+        "updated": ticket.to_updated.naive_utc().format(DATE_FORMAT).to_string(),
+        "created": ticket.to_created.map(|created| created.naive_utc().format(DATE_FORMAT).to_string()),
+        "store_url": ticket.to_store_url.clone().unwrap_or_default(),
+        "store_info": ticket.to_store_info.clone().unwrap_or_default(),
+        "printed": ticket.print(None),
+        "minimal": ticket.print_minimal(),
+        "labelled": ticket.print_labelled(),
+    })
+}
+
+/// Context of card templates:
+/// `title`, `label`, `creators` (names), `card` (all card fields), `json` (the data of the TO),
+/// `ticket` (the ticket context of the TO) and `to` (metadata of the TO: `ticket_id`, `source_id`, `source_name`,
+/// `source_id_type`, `source_path`, `store_url`, `store_info`, `created`, `updated` and `version`)
+pub fn card_context(textual_object: &TextualObject) -> Value {
+    let card = &textual_object.card.0;
+    let title = Some(card.title().trim())
+        .filter(|title| !title.is_empty())
+        .or_else(|| textual_object.json.0.get("title").and_then(|title| title.as_str()))
+        .unwrap_or(&textual_object.ticket_id);
+    let creators: Vec<&str> = card.creators().values().map(|person| person.name.as_str()).collect();
+    json!({
+        "title": title,
+        "label": textual_object.display_label().unwrap_or_else(|| textual_object.ticket_id.clone()),
+        "creators": creators,
+        "card": card.to_json(),
+        "json": textual_object.json.0,
+        "ticket": ticket_context(&ToTicket::from(textual_object.clone())),
+        "to": {
+            "ticket_id": textual_object.ticket_id,
+            "source_id": textual_object.source_id,
+            "source_name": textual_object.source_name,
+            "source_id_type": textual_object.source_id_type,
+            "source_path": textual_object.source_path,
+            "store_url": textual_object.store_url,
+            "store_info": textual_object.store_info,
+            "created": textual_object.created.format(DATE_FORMAT).to_string(),
+            "updated": textual_object.updated.format(DATE_FORMAT).to_string(),
+            "version": textual_object.version,
+        },
+    })
+}
+
+/// Context of tag templates: `key`, `value`, `note`, and the tag as printed: `printed`
+pub fn tag_context(tag: &ToTag) -> Value {
+    json!({
+        "key": tag.key,
+        "value": tag.value.clone().unwrap_or_default(),
+        "note": tag.note.clone().unwrap_or_default(),
+        "printed": tag.print_tag(None),
+    })
+}
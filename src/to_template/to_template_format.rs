@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Document formats templates render to; values put in a template with `{{...}}` are escaped for the format
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ToTemplateFormat {
+    Markdown,
+    Html,
+    Latex,
+}
+
+impl ToTemplateFormat {
+    /// extension of template files in this format, e.g. `ticket.md.hbs`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ToTemplateFormat::Markdown => "md",
+            ToTemplateFormat::Html => "html",
+            ToTemplateFormat::Latex => "tex",
+        }
+    }
+
+    /// escape a text so that it is read literally in a document of this format
+    pub fn escape(&self, text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match (self, c) {
+                (ToTemplateFormat::Markdown, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                (ToTemplateFormat::Html, '&') => escaped.push_str("&amp;"),
+                (ToTemplateFormat::Html, '<') => escaped.push_str("&lt;"),
+                (ToTemplateFormat::Html, '>') => escaped.push_str("&gt;"),
+                (ToTemplateFormat::Html, '"') => escaped.push_str("&quot;"),
+                (ToTemplateFormat::Html, '\'') => escaped.push_str("&#39;"),
+                (ToTemplateFormat::Latex, '\\') => escaped.push_str("\\textbackslash{}"),
+                (ToTemplateFormat::Latex, '~') => escaped.push_str("\\textasciitilde{}"),
+                (ToTemplateFormat::Latex, '^') => escaped.push_str("\\textasciicircum{}"),
+                (ToTemplateFormat::Latex, '&' | '%' | '$' | '#' | '_' | '{' | '}') => {
+                    escaped.push('\\');
+                    escaped.push(c);
+                }
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(ToTemplateFormat::Markdown.escape("[[id: a_b]]"), "\\[\\[id: a\\_b\\]\\]");
+        assert_eq!(ToTemplateFormat::Html.escape("<a href=\"x\">&</a>"), "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;");
+        assert_eq!(ToTemplateFormat::Latex.escape("50% of $5 {a_b} \\ ~"), "50\\% of \\$5 \\{a\\_b\\} \\textbackslash{} \\textasciitilde{}");
+    }
+}
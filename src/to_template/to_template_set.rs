@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::to::to_struct::TextualObject;
+use crate::to_tag::to_tag_struct::ToTag;
+use crate::to_template::to_template_context::{card_context, tag_context, ticket_context};
+use crate::to_template::to_template_format::ToTemplateFormat;
+use crate::to_template::to_template_struct::ToTemplate;
+use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
+use crate::to_ticket::to_ticket_struct::ToTicket;
+
+// directory of template files, next to the store file
+pub const TEMPLATE_DIRECTORY: &str = "to_templates";
+
+/// What a template renders
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToTemplateKind {
+    Ticket,
+    Label,
+    Card,
+    Tag,
+}
+
+impl ToTemplateKind {
+    /// name of the template file of this kind, e.g. `ticket.md.hbs`
+    pub fn file_name(&self, format: ToTemplateFormat) -> String {
+        let name = match self {
+            ToTemplateKind::Ticket => "ticket",
+            ToTemplateKind::Label => "label",
+            ToTemplateKind::Card => "card",
+            ToTemplateKind::Tag => "tag",
+        };
+        format!("{}.{}.hbs", name, format.extension())
+    }
+
+    // template used when no file is given
+    fn default_source(&self, format: ToTemplateFormat) -> &'static str {
+        match (self, format) {
+            (ToTemplateKind::Ticket, ToTemplateFormat::Markdown) => "{{{printed}}}",
+            (ToTemplateKind::Ticket, ToTemplateFormat::Html) => "<span class=\"to-ticket\" data-ticket-id=\"{{id}}\">{{printed}}</span>",
+            (ToTemplateKind::Ticket, ToTemplateFormat::Latex) => "{\\ttfamily {{printed}}}",
+            (ToTemplateKind::Label, ToTemplateFormat::Markdown) => "{{#if label}}{{label}}{{else}}{{id}}{{/if}}",
+            (ToTemplateKind::Label, ToTemplateFormat::Html) => "<cite data-ticket-id=\"{{id}}\">{{#if label}}{{label}}{{else}}{{id}}{{/if}}</cite>",
+            (ToTemplateKind::Label, ToTemplateFormat::Latex) => "{\\itshape {{#if label}}{{label}}{{else}}{{id}}{{/if}}}",
+            (ToTemplateKind::Card, ToTemplateFormat::Markdown) => {
+                "**{{title}}**{{#each creators}}{{#if @first}} by {{else}}, {{/if}}{{this}}{{/each}}{{#if card.description}}\n\n{{card.description}}{{/if}}\n\n{{{ticket.minimal}}}"
+            }
+            (ToTemplateKind::Card, ToTemplateFormat::Html) => {
+                "<div class=\"to-card\" data-ticket-id=\"{{to.ticket_id}}\"><strong>{{title}}</strong>{{#each creators}}{{#if @first}} by {{else}}, {{/if}}{{this}}{{/each}}{{#if card.description}}<p>{{card.description}}</p>{{/if}}</div>"
+            }
+            (ToTemplateKind::Card, ToTemplateFormat::Latex) => {
+                "{\\bfseries {{title}}}{{#each creators}}{{#if @first}} by {{else}}, {{/if}}{{this}}{{/each}}{{#if card.description}}\\par {{card.description}}{{/if}}"
+            }
+            (ToTemplateKind::Tag, ToTemplateFormat::Markdown) => "{{{printed}}}",
+            (ToTemplateKind::Tag, ToTemplateFormat::Html) => "<span class=\"to-tag\">{{key}}{{#if value}}: {{value}}{{/if}}{{#if note}} ({{note}}){{/if}}</span>",
+            (ToTemplateKind::Tag, ToTemplateFormat::Latex) => "\\textsc{ {{~key}}}{{#if value}}: {{value}}{{/if}}{{#if note}} ({{note}}){{/if}}",
+        }
+    }
+}
+
+/// Templates of tickets, labels, cards and tags in a format.
+/// Each project can set its own in files next to its store, see `for_store`; the others are the defaults.
+#[derive(Clone, Debug)]
+pub struct ToTemplateSet {
+    pub format: ToTemplateFormat,
+    pub ticket: ToTemplate,
+    pub label: ToTemplate,
+    pub card: ToTemplate,
+    pub tag: ToTemplate,
+}
+
+impl ToTemplateSet {
+    /// built-in templates of a format
+    pub fn new(format: ToTemplateFormat) -> Self {
+        // the built-in templates are valid
+        let default = |kind: ToTemplateKind| ToTemplate::parse(kind.default_source(format)).unwrap();
+        ToTemplateSet {
+            format,
+            ticket: default(ToTemplateKind::Ticket),
+            label: default(ToTemplateKind::Label),
+            card: default(ToTemplateKind::Card),
+            tag: default(ToTemplateKind::Tag),
+        }
+    }
+
+    /// templates read from a directory, e.g. `card.html.hbs`; the built-in template for every missing file
+    pub fn from_dir(directory: &Path, format: ToTemplateFormat) -> Result<Self, ToErrors> {
+        let mut templates = ToTemplateSet::new(format);
+        for kind in [ToTemplateKind::Ticket, ToTemplateKind::Label, ToTemplateKind::Card, ToTemplateKind::Tag] {
+            let path = directory.join(kind.file_name(format));
+            if !path.is_file() {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).map_err(|e| ToErrors::IoError(TextualObjectErrorMessage {
+                message: format!("Cannot read template file {}: {}", path.display(), e),
+                ..Default::default()
+            }))?;
+            let template = ToTemplate::parse(&source).map_err(|e| match e {
+                ToErrors::ParseError(mut error_message) => {
+                    error_message.suggestion = format!("Check the template file {}", path.display());
+                    ToErrors::ParseError(error_message)
+                }
+                other => other,
+            })?;
+            match kind {
+                ToTemplateKind::Ticket => templates.ticket = template,
+                ToTemplateKind::Label => templates.label = template,
+                ToTemplateKind::Card => templates.card = template,
+                ToTemplateKind::Tag => templates.tag = template,
+            }
+        }
+        Ok(templates)
+    }
+
+    /// templates in the `to_templates` directory next to a store file
+    pub fn for_store(store_url: &str, format: ToTemplateFormat) -> Result<Self, ToErrors> {
+        let store_directory = Path::new(store_url).parent().map(Path::to_path_buf).unwrap_or_default();
+        ToTemplateSet::from_dir(&store_directory.join(TEMPLATE_DIRECTORY), format)
+    }
+
+    pub fn render_ticket(&self, ticket: &ToTicket) -> String {
+        self.print_ticket(ticket, None)
+    }
+
+    /// the ticket rendered with the ticket template, its `printed` being the ticket printed with the options
+    pub fn print_ticket(&self, ticket: &ToTicket, opt: Option<ToTicketPrintOption>) -> String {
+        let mut context = ticket_context(ticket);
+        context["printed"] = Value::from(ticket.print(opt));
+        self.ticket.render(&context, self.format)
+    }
+
+    pub fn render_label(&self, ticket: &ToTicket) -> String {
+        self.label.render(&ticket_context(ticket), self.format)
+    }
+
+    /// summary of the card of a TO
+    pub fn render_card(&self, textual_object: &TextualObject) -> String {
+        self.card.render(&card_context(textual_object), self.format)
+    }
+
+    pub fn render_tag(&self, tag: &ToTag) -> String {
+        self.tag.render(&tag_context(tag), self.format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::to_ticket::to_ticket_value::ToTicketValue;
+    use crate::utils::id_generator::generate_id;
+
+    use super::*;
+
+    fn discipline_and_punish() -> TextualObject {
+        TextualObject {
+            ticket_id: "abc12".to_string(),
+            json: sqlx::types::Json(json!({"title": "Discipline & Punish", "author": "Michel Foucault", "year": 1975})),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_templates() {
+        let to = discipline_and_punish();
+        let mut ticket = ToTicket::from(to.clone());
+        ticket.values = Default::default();
+        ticket.add_value("page".to_string(), ToTicketValue::Number(12.into()));
+
+        let markdown = ToTemplateSet::new(ToTemplateFormat::Markdown);
+        assert_eq!(markdown.render_label(&ticket), "Foucault 1975, Discipline & Punish");
+        assert!(markdown.render_ticket(&ticket).starts_with("[[id: abc12 | page: 12 |"));
+        assert!(markdown.render_card(&to).starts_with("**Discipline & Punish**"));
+        assert!(markdown.render_card(&to).ends_with("[[id: abc12]]"));
+
+        let html = ToTemplateSet::new(ToTemplateFormat::Html);
+        assert_eq!(html.render_label(&ticket), "<cite data-ticket-id=\"abc12\">Foucault 1975, Discipline &amp; Punish</cite>");
+
+        let latex = ToTemplateSet::new(ToTemplateFormat::Latex);
+        assert_eq!(latex.render_label(&ticket), "{\\itshape Foucault 1975, Discipline \\& Punish}");
+        assert_eq!(latex.render_card(&to), "{\\bfseries Discipline \\& Punish}");
+
+        // tickets print the same without templates and with the built-in markdown ones
+        let templated = markdown.print_ticket(&ticket, Some(ToTicketPrintOption {
+            minimal: true,
+            ..Default::default()
+        }));
+        assert_eq!(templated, ticket.print_minimal());
+    }
+
+    #[test]
+    fn test_tag_templates() {
+        let (_, tags) = ToTag::scan_text_for_tags("[[Foucault|discipline|p. 12]]");
+        assert_eq!(ToTemplateSet::new(ToTemplateFormat::Markdown).render_tag(&tags[0]), tags[0].print_tag(None));
+        assert_eq!(ToTemplateSet::new(ToTemplateFormat::Html).render_tag(&tags[0]), "<span class=\"to-tag\">Foucault: discipline (p. 12)</span>");
+        assert_eq!(ToTemplateSet::new(ToTemplateFormat::Latex).render_tag(&tags[0]), "\\textsc{Foucault}: discipline (p. 12)");
+    }
+
+    #[test]
+    fn test_templates_next_to_store() {
+        let directory = std::env::temp_dir().join(format!("to_templates_test_{}", generate_id()));
+        std::fs::create_dir_all(directory.join(TEMPLATE_DIRECTORY)).unwrap();
+        let store_url = directory.join("_to_store.db");
+        std::fs::write(directory.join(TEMPLATE_DIRECTORY).join("ticket.md.hbs"), "[{{label}}](to:{{id}}){{#if values.page}}, p. {{values.page}}{{/if}}").unwrap();
+
+        let to = discipline_and_punish();
+        let mut ticket = ToTicket::from(to.clone());
+        ticket.values = Default::default();
+        ticket.add_value("page".to_string(), ToTicketValue::Number(12.into()));
+        let templates = ToTemplateSet::for_store(store_url.to_str().unwrap(), ToTemplateFormat::Markdown).unwrap();
+        assert_eq!(templates.render_ticket(&ticket), "[Foucault 1975, Discipline & Punish](to:abc12), p. 12");
+        // other templates are the built-in ones
+        assert_eq!(templates.render_label(&ticket), ToTemplateSet::new(ToTemplateFormat::Markdown).render_label(&ticket));
+
+        // tickets are printed with the template
+        let printed = templates.print_ticket(&ticket, None);
+        assert_eq!(printed, "[Foucault 1975, Discipline & Punish](to:abc12), p. 12");
+
+        std::fs::write(directory.join(TEMPLATE_DIRECTORY).join("card.md.hbs"), "{{#if title}}{{title}}").unwrap();
+        assert!(matches!(ToTemplateSet::for_store(store_url.to_str().unwrap(), ToTemplateFormat::Markdown), Err(ToErrors::ParseError(_))));
+        // a file that cannot be read is not a template error
+        std::fs::write(directory.join(TEMPLATE_DIRECTORY).join("card.md.hbs"), [0xff, 0xfe]).unwrap();
+        assert!(matches!(ToTemplateSet::for_store(store_url.to_str().unwrap(), ToTemplateFormat::Markdown), Err(ToErrors::IoError(_))));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
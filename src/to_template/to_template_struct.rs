@@ -0,0 +1,314 @@
+use serde_json::Value;
+
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::to_template::to_template_format::ToTemplateFormat;
+
+/// A handlebars-style template:
+/// * `{{path}}` puts the value at a dot separated path, escaped for the format, e.g. `{{values.page}}`
+/// * `{{{path}}}` puts the value as it is
+/// * `{{#if path}}...{{else}}...{{/if}}` renders the first part when the value is not empty, false or zero
+/// * `{{#each path}}...{{/each}}` renders its body for each item of a list or entry of an object,
+///   with the item as `this` and `@index`, `@key`, `@first` and `@last`
+/// * `{{! comment }}` is left out
+/// * `~` at the start or end of a tag removes the whitespace before or after it, e.g. `{{~path}}`
+///
+/// Lists are rendered with their items separated by commas; missing values are rendered empty.
+/// A brace right before a tag reads as a raw tag, so a LaTeX group around a value is written with
+/// a space removed by `~`, e.g. `\emph{ {{~title}}}` for `\emph{{{title}}}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToTemplate {
+    nodes: Vec<ToTemplateNode>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ToTemplateNode {
+    Text(String),
+    Value { path: String, raw: bool },
+    If { path: String, then: Vec<ToTemplateNode>, otherwise: Vec<ToTemplateNode> },
+    Each { path: String, body: Vec<ToTemplateNode>, otherwise: Vec<ToTemplateNode> },
+}
+
+// a tag or text of the template source
+enum Token<'a> {
+    Text(&'a str),
+    Value { path: &'a str, raw: bool },
+    Open { block: &'a str, path: &'a str },
+    Else,
+    Close(&'a str),
+}
+
+// scope of a value in the template: the value and, within each, the position of the item
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+    key: Option<&'a str>,
+    last: bool,
+}
+
+impl ToTemplate {
+    /// read a template, or a parse error on an unclosed tag or block
+    pub fn parse(source: &str) -> Result<Self, ToErrors> {
+        let tokens = tokenize(source)?;
+        let mut tokens = tokens.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        match end {
+            End::Source => Ok(ToTemplate { nodes }),
+            End::Else => Err(template_error("{{else}} outside of a block".to_string())),
+            End::Close(block) => Err(template_error(format!("{{{{/{}}}}} without a matching {{{{#{}}}}}", block, block))),
+        }
+    }
+
+    /// render the template with a context, values escaped for the format
+    pub fn render(&self, context: &Value, format: ToTemplateFormat) -> String {
+        let mut rendered = String::new();
+        let scopes = vec![Scope { value: context, index: None, key: None, last: false }];
+        render_nodes(&self.nodes, &scopes, format, &mut rendered);
+        rendered
+    }
+}
+
+fn template_error(message: String) -> ToErrors {
+    ToErrors::ParseError(TextualObjectErrorMessage {
+        message: format!("Cannot read template: {}", message),
+        ..Default::default()
+    })
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>, ToErrors> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    // the previous tag ends with `~`
+    let mut trim_next = false;
+    while let Some(start) = rest.find("{{") {
+        let raw = rest[start..].starts_with("{{{");
+        let (open, close) = if raw { ("{{{", "}}}") } else { ("{{", "}}") };
+        let content_start = start + open.len();
+        let content_end = match rest[content_start..].find(close) {
+            Some(end) => content_start + end,
+            None => return Err(template_error(format!("unclosed tag at \"{}\"", &rest[start..]))),
+        };
+        let mut content = rest[content_start..content_end].trim();
+        let mut text = &rest[..start];
+        if trim_next {
+            text = text.trim_start();
+        }
+        if let Some(trimmed) = content.strip_prefix('~') {
+            text = text.trim_end();
+            content = trimmed.trim_start();
+        }
+        trim_next = content.ends_with('~');
+        if trim_next {
+            content = content[..content.len() - 1].trim_end();
+        }
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        rest = &rest[content_end + close.len()..];
+
+        if raw {
+            tokens.push(Token::Value { path: content, raw: true });
+        } else if content.starts_with('!') {
+            continue;
+        } else if let Some(block) = content.strip_prefix('#') {
+            let (block, path) = block.split_once(char::is_whitespace).unwrap_or((block, ""));
+            tokens.push(Token::Open { block, path: path.trim() });
+        } else if let Some(block) = content.strip_prefix('/') {
+            tokens.push(Token::Close(block.trim()));
+        } else if content == "else" {
+            tokens.push(Token::Else);
+        } else {
+            tokens.push(Token::Value { path: content, raw: false });
+        }
+    }
+    if trim_next {
+        rest = rest.trim_start();
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+// what ended a list of nodes
+enum End<'a> {
+    Source,
+    Else,
+    Close(&'a str),
+}
+
+fn parse_nodes<'a>(tokens: &mut impl Iterator<Item = Token<'a>>) -> Result<(Vec<ToTemplateNode>, End<'a>), ToErrors> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(ToTemplateNode::Text(text.to_string())),
+            Token::Value { path, raw } => nodes.push(ToTemplateNode::Value { path: path.to_string(), raw }),
+            Token::Else => return Ok((nodes, End::Else)),
+            Token::Close(block) => return Ok((nodes, End::Close(block))),
+            Token::Open { block, path } => {
+                if block != "if" && block != "each" {
+                    return Err(template_error(format!("unknown block {{{{#{}}}}}", block)));
+                }
+                let (body, mut end) = parse_nodes(tokens)?;
+                let mut otherwise = Vec::new();
+                if let End::Else = end {
+                    let (else_body, else_end) = parse_nodes(tokens)?;
+                    otherwise = else_body;
+                    end = else_end;
+                }
+                match end {
+                    End::Close(closed) if closed == block => {}
+                    _ => return Err(template_error(format!("{{{{#{} {}}}}} is not closed by {{{{/{}}}}}", block, path, block))),
+                }
+                let path = path.to_string();
+                nodes.push(match block {
+                    "if" => ToTemplateNode::If { path, then: body, otherwise },
+                    _ => ToTemplateNode::Each { path, body, otherwise },
+                });
+            }
+        }
+    }
+    Ok((nodes, End::Source))
+}
+
+fn render_nodes(nodes: &[ToTemplateNode], scopes: &[Scope], format: ToTemplateFormat, rendered: &mut String) {
+    for node in nodes {
+        match node {
+            ToTemplateNode::Text(text) => rendered.push_str(text),
+            ToTemplateNode::Value { path, raw } => {
+                let text = lookup(scopes, path).map(plain_text).unwrap_or_default();
+                if *raw {
+                    rendered.push_str(&text);
+                } else {
+                    rendered.push_str(&format.escape(&text));
+                }
+            }
+            ToTemplateNode::If { path, then, otherwise } => {
+                let branch = if lookup(scopes, path).is_some_and(is_truthy) { then } else { otherwise };
+                render_nodes(branch, scopes, format, rendered);
+            }
+            ToTemplateNode::Each { path, body, otherwise } => {
+                let list = lookup(scopes, path);
+                let items: Vec<(Option<&str>, &Value)> = match &list {
+                    Some(Value::Array(items)) => items.iter().map(|item| (None, item)).collect(),
+                    Some(Value::Object(map)) => map.iter().map(|(key, item)| (Some(key.as_str()), item)).collect(),
+                    _ => Vec::new(),
+                };
+                if items.is_empty() {
+                    render_nodes(otherwise, scopes, format, rendered);
+                }
+                let count = items.len();
+                for (index, (key, value)) in items.into_iter().enumerate() {
+                    let mut item_scopes = scopes.to_vec();
+                    item_scopes.push(Scope { value, index: Some(index), key, last: index + 1 == count });
+                    render_nodes(body, &item_scopes, format, rendered);
+                }
+            }
+        }
+    }
+}
+
+// value at a path: `this`, `@index`-like data of the current item, `../` for the outer scope,
+// otherwise a dot separated path looked up from the innermost scope outwards
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> Option<Value> {
+    let mut depth = scopes.len();
+    let mut path = path.trim();
+    while let Some(rest) = path.strip_prefix("../") {
+        depth = depth.saturating_sub(1).max(1);
+        path = rest;
+    }
+    let scopes = &scopes[..depth];
+    let current = scopes.last()?;
+    match path {
+        "@index" => return current.index.map(Value::from),
+        "@key" => return current.key.map(Value::from),
+        "@first" => return current.index.map(|index| Value::Bool(index == 0)),
+        "@last" => return current.index.map(|_| Value::Bool(current.last)),
+        "this" | "." => return Some(current.value.clone()),
+        _ => {}
+    }
+    if let Some(rest) = path.strip_prefix("this.") {
+        return select(current.value, rest).cloned();
+    }
+    scopes.iter().rev().find_map(|scope| select(scope.value, path)).cloned()
+}
+
+fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => None,
+    })
+}
+
+fn plain_text(value: Value) -> String {
+    match value {
+        Value::Null | Value::Object(_) => String::new(),
+        Value::String(text) => text,
+        Value::Array(items) => {
+            let items: Vec<String> = items.into_iter().map(plain_text).filter(|item| !item.is_empty()).collect();
+            items.join(", ")
+        }
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn render(source: &str, context: Value) -> String {
+        ToTemplate::parse(source).unwrap().render(&context, ToTemplateFormat::Html)
+    }
+
+    #[test]
+    fn test_values() {
+        let context = json!({"title": "A & B", "values": {"page": 12, "tags": ["a", "b"]}, "missing": null});
+        assert_eq!(render("{{title}} p. {{values.page}} [{{values.tags}}]{{missing}}{{nothing.here}}", context.clone()), "A &amp; B p. 12 [a, b]");
+        assert_eq!(render("{{{title}}}{{! not rendered }}", context), "A & B");
+    }
+
+    #[test]
+    fn test_blocks() {
+        let context = json!({"label": "", "id": "abc", "creators": ["Foucault", "Sheridan"], "values": {"a": 1, "b": 2}});
+        assert_eq!(render("{{#if label}}{{label}}{{else}}{{id}}{{/if}}", context.clone()), "abc");
+        assert_eq!(render("{{#each creators}}{{#if @first}}by {{else}}, {{/if}}{{this}}{{/each}}", context.clone()), "by Foucault, Sheridan");
+        assert_eq!(render("{{#each values}}{{@key}}={{this}}{{#if @last}}.{{else}};{{/if}}{{/each}}", context.clone()), "a=1;b=2.");
+        // outer values are found from within each
+        assert_eq!(render("{{#each creators}}{{id}}-{{@index}} {{/each}}", context.clone()), "abc-0 abc-1 ");
+        assert_eq!(render("{{#each nothing}}x{{else}}none{{/each}}", context), "none");
+    }
+
+    #[test]
+    fn test_whitespace_control() {
+        let context = json!({"title": "Surveiller & punir", "creators": ["Foucault", "Sheridan"]});
+        assert_eq!(render("<ul>\n  {{~#each creators~}}\n  <li>{{this}}</li>\n  {{~/each~}}\n</ul>", context.clone()), "<ul><li>Foucault</li><li>Sheridan</li></ul>");
+        // a LaTeX group around a value, which written `\emph{{{title}}}` would read as a raw tag
+        let latex = ToTemplate::parse("\\emph{ {{~title}}}").unwrap();
+        assert_eq!(latex.render(&context, ToTemplateFormat::Latex), "\\emph{Surveiller \\& punir}");
+        assert_eq!(render("a {{{~title~}}} b", context), "aSurveiller & punirb");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(ToTemplate::parse("{{#if a}}x").is_err());
+        assert!(ToTemplate::parse("{{#if a}}x{{/each}}").is_err());
+        assert!(ToTemplate::parse("x{{/if}}").is_err());
+        assert!(ToTemplate::parse("{{a").is_err());
+        assert!(ToTemplate::parse("{{#with a}}x{{/with}}").is_err());
+        assert!(ToTemplate::parse("{\\bfseries {{title}}}").is_ok());
+    }
+}
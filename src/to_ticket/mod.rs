@@ -5,3 +5,6 @@ pub mod to_ticket_position;
 pub mod to_ticket_parse;
 pub mod to_ticket_write;
 pub mod to_ticket_utils;
+pub mod to_ticket_value;
+pub mod to_ticket_spec;
+pub mod to_ticket_projection;
@@ -1,11 +1,20 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct ToMarker {
     pub left_marker: String,
     pub right_marker: String,
     pub value_entry_separator: String,
     pub value_separator: String,
+    // character that makes the next character literal, e.g. `\|` is a `|` inside a value rather than a separator.
+    // `\n` and `\r` stand for line breaks. Before any other character it is literal, e.g. in `C:\path`.
+    #[serde(default = "default_escape_character")]
+    pub escape_character: char,
+}
+
+fn default_escape_character() -> char {
+    '\\'
 }
 
 // create default ToMarker
@@ -16,6 +25,244 @@ impl Default for ToMarker {
             right_marker: String::from("]]"),
             value_entry_separator: String::from("|"),
             value_separator: String::from(":"),
+            escape_character: default_escape_character(),
+        }
+    }
+}
+
+/// Ways a ticket can be written in a text
+#[derive(Deserialize, Serialize, ToSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToMarkerDialect {
+    // [[id:abc]], or whatever markers the parser option sets
+    #[default]
+    DoubleSquare,
+    // {{id:abc}}
+    DoubleCurly,
+    // 【【id:abc】】, as CJK input methods produce for [[
+    FullWidthLenticular,
+    // ［［id:abc］］, full-width square brackets
+    FullWidthSquare,
+    // [label](to:abc), a markdown link to the TO
+    MarkdownLink,
+}
+
+impl ToMarkerDialect {
+    /// all dialects
+    pub fn all() -> Vec<ToMarkerDialect> {
+        vec![
+            ToMarkerDialect::DoubleSquare,
+            ToMarkerDialect::DoubleCurly,
+            ToMarkerDialect::FullWidthLenticular,
+            ToMarkerDialect::FullWidthSquare,
+            ToMarkerDialect::MarkdownLink,
+        ]
+    }
+
+    /// marker of a bracket dialect, with the separators of `base`; None for markdown links, which have no separators
+    pub fn marker(&self, base: &ToMarker) -> Option<ToMarker> {
+        let (left_marker, right_marker) = match self {
+            ToMarkerDialect::DoubleSquare => return Some(base.clone()),
+            ToMarkerDialect::DoubleCurly => ("{{", "}}"),
+            ToMarkerDialect::FullWidthLenticular => ("【【", "】】"),
+            ToMarkerDialect::FullWidthSquare => ("［［", "］］"),
+            ToMarkerDialect::MarkdownLink => return None,
+        };
+        Some(ToMarker {
+            left_marker: left_marker.to_string(),
+            right_marker: right_marker.to_string(),
+            ..base.clone()
+        })
+    }
+}
+
+impl ToMarker {
+    /// escape a value so that markers, entry separators and line breaks in it survive printing and scanning
+    pub fn escape_value(&self, value: &str) -> String {
+        self.escape(value, &[&self.left_marker, &self.right_marker, &self.value_entry_separator])
+    }
+
+    /// escape a key, which in addition cannot contain the value separator
+    pub fn escape_key(&self, key: &str) -> String {
+        self.escape(key, &[&self.left_marker, &self.right_marker, &self.value_entry_separator, &self.value_separator])
+    }
+
+    /// escape a user key that the ticket spec reserves, e.g. `\updated`, so that it is read back as a value rather than as meta-data
+    pub fn escape_reserved_key(&self, key: &str) -> String {
+        format!("{}{}", self.escape_character, self.escape_key(key))
+    }
+
+    /// the key of an entry written by `escape_reserved_key`, still escaped; None for other keys
+    pub fn strip_reserved_escape<'a>(&self, written_key: &'a str) -> Option<&'a str> {
+        let key = written_key.strip_prefix(self.escape_character)?;
+        // an escaped escape character or separator starts an ordinary key
+        match key.chars().next() {
+            Some(c) if !self.is_escaped(c) => Some(key),
+            _ => None,
+        }
+    }
+
+    // escape every character that is part of any of the special strings
+    fn escape(&self, text: &str, special: &[&str]) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    escaped.push(self.escape_character);
+                    escaped.push('n');
+                }
+                '\r' => {
+                    escaped.push(self.escape_character);
+                    escaped.push('r');
+                }
+                c if c == self.escape_character || special.iter().any(|s| s.contains(c)) => {
+                    escaped.push(self.escape_character);
+                    escaped.push(c);
+                }
+                c => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    /// Undo `escape_value` and `escape_key`.
+    /// Only the characters that `escape` escapes are unescaped, so that an escape character written before
+    /// any other character, as in a Windows path, is kept as it is
+    pub fn unescape(&self, text: &str) -> String {
+        let mut unescaped = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != self.escape_character {
+                unescaped.push(c);
+                continue;
+            }
+            match chars.peek().copied() {
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some(escaped) if self.is_escaped(escaped) => unescaped.push(escaped),
+                // a trailing escape character, or one before a character that is never escaped, is kept as it is
+                _ => {
+                    unescaped.push(c);
+                    continue;
+                }
+            }
+            chars.next();
         }
+        unescaped
     }
-}
\ No newline at end of file
+
+    // whether `escape` writes the character escaped
+    fn is_escaped(&self, c: char) -> bool {
+        c == self.escape_character
+            || [&self.left_marker, &self.right_marker, &self.value_entry_separator, &self.value_separator].iter().any(|s| s.contains(c))
+    }
+
+    /// split on the separator wherever it is not escaped, into at most `max_pieces` pieces.
+    /// Pieces are returned still escaped.
+    pub fn split_unescaped<'a>(&self, text: &'a str, separator: &str, max_pieces: usize) -> Vec<&'a str> {
+        let mut pieces = Vec::new();
+        let mut piece_start = 0;
+        let mut chars = text.char_indices();
+        while let Some((index, c)) = chars.next() {
+            if separator.is_empty() || pieces.len() + 1 >= max_pieces {
+                break;
+            }
+            if c == self.escape_character {
+                chars.next();
+                continue;
+            }
+            if text[index..].starts_with(separator) {
+                pieces.push(&text[piece_start..index]);
+                piece_start = index + separator.len();
+                // skip the rest of the separator
+                for _ in 1..separator.chars().count() {
+                    chars.next();
+                }
+            }
+        }
+        pieces.push(&text[piece_start..]);
+        pieces
+    }
+
+    /// whether the text ends with an escape character that escapes whatever follows it
+    pub fn ends_with_escape(&self, text: &str) -> bool {
+        text.chars().rev().take_while(|c| *c == self.escape_character).count() % 2 == 1
+    }
+}
+
+// test
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_and_unescape_value() {
+        let marker = ToMarker::default();
+        let value = "Discipline: the Birth | of the [[Prison]]\nC:\\path";
+        let escaped = marker.escape_value(value);
+        assert_eq!(escaped, "Discipline: the Birth \\| of the \\[\\[Prison\\]\\]\\nC:\\\\path");
+        assert!(!escaped.contains('\n'));
+        assert_eq!(marker.unescape(&escaped), value);
+    }
+
+    #[test]
+    fn test_unescape_keeps_other_escape_characters() {
+        let marker = ToMarker::default();
+        assert_eq!(marker.unescape("C:\\path\\to"), "C:\\path\\to");
+        assert_eq!(marker.unescape("a\\|b \\\\ c\\"), "a|b \\ c\\");
+        // the markers of the ticket are escaped, whatever they are
+        let curly = ToMarker {
+            left_marker: "{{".to_string(),
+            right_marker: "}}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(curly.unescape("\\{\\[x"), "{\\[x");
+    }
+
+    #[test]
+    fn test_escape_key() {
+        let marker = ToMarker::default();
+        assert_eq!(marker.escape_key("a:b"), "a\\:b");
+        assert_eq!(marker.unescape(&marker.escape_key("a:b")), "a:b");
+    }
+
+    #[test]
+    fn test_escape_reserved_key() {
+        let marker = ToMarker::default();
+        assert_eq!(marker.escape_reserved_key("updated"), "\\updated");
+        assert_eq!(marker.strip_reserved_escape("\\updated"), Some("updated"));
+        assert_eq!(marker.strip_reserved_escape("updated"), None);
+        assert_eq!(marker.strip_reserved_escape("\\\\updated"), None);
+        assert_eq!(marker.strip_reserved_escape("\\|x"), None);
+    }
+
+    #[test]
+    fn test_split_unescaped() {
+        let marker = ToMarker::default();
+        assert_eq!(marker.split_unescaped("a|b\\|c|d", "|", usize::MAX), vec!["a", "b\\|c", "d"]);
+        assert_eq!(marker.split_unescaped("key\\:1:value:2", ":", 2), vec!["key\\:1", "value:2"]);
+        assert_eq!(marker.split_unescaped("a\\\\|b", "|", usize::MAX), vec!["a\\\\", "b"]);
+        assert_eq!(marker.split_unescaped("", "|", usize::MAX), vec![""]);
+    }
+
+    #[test]
+    fn test_dialect_marker() {
+        let base = ToMarker {
+            value_entry_separator: ";".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(ToMarkerDialect::DoubleSquare.marker(&base), Some(base.clone()));
+        let full_width = ToMarkerDialect::FullWidthLenticular.marker(&base).unwrap();
+        assert_eq!(full_width.left_marker, "【【");
+        assert_eq!(full_width.value_entry_separator, ";");
+        assert_eq!(ToMarkerDialect::MarkdownLink.marker(&base), None);
+    }
+
+    #[test]
+    fn test_ends_with_escape() {
+        let marker = ToMarker::default();
+        assert!(marker.ends_with_escape("a\\"));
+        assert!(!marker.ends_with_escape("a\\\\"));
+        assert!(!marker.ends_with_escape("a"));
+    }
+}
@@ -1,4 +1,6 @@
 
+use crate::to_ticket::to_ticket_spec::ToTicketSpec;
+
 pub struct ToTicketPrintOption {
     // whether include updated
     pub include_updated: bool,
@@ -8,8 +10,12 @@ pub struct ToTicketPrintOption {
     pub include_store_info: bool,
     // whether include store_id
     pub include_store_id: bool,
-    // this will overwrite other options and print only a ticket with a single ticket id field
+    // whether include the display label, if the ticket has one and the spec reserves labels
+    pub include_label: bool,
+    // this will overwrite other options and print only a ticket with a single ticket id field, and the label if include_label
     pub minimal: bool,
+    // keys of the reserved fields
+    pub ticket_spec: ToTicketSpec,
 }
 
 // create default values for ToTicketPrintOption
@@ -21,7 +27,9 @@ impl Default for ToTicketPrintOption {
             include_created: true,
             include_store_info: true,
             include_store_id: true,
+            include_label: false,
             minimal: false,
+            ticket_spec: ToTicketSpec::default(),
         }
     }
 }
\ No newline at end of file
@@ -1,34 +1,54 @@
-use chrono::{DateTime, FixedOffset, NaiveDateTime};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
 
+use crate::error::{TextualObjectErrorMessage, ToErrors};
+use crate::to_parser::parser_diagnostic::{ToDiagnosticKind, ToParseDiagnostic};
 use crate::to_parser::parser_option::ToParserOption;
 use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::to_ticket::to_ticket_spec::ToTicketField;
 use crate::to_ticket::to_ticket_struct::ToTicket;
+use crate::to_ticket::to_ticket_value::ToTicketValue;
 
 impl ToTicket {
     /// Parse single ticket from text
     /// # Arguments
     /// * `marked_content_only` - raw text to parse
-    pub fn parse(marked_content_only: &str, opt: &ToParserOption, intext_position: Option<ToTicketPositionInfo>) -> Self {
-
-        // remove left and right markers if they exist
-        let mut clean_content = marked_content_only.replace(&opt.to_marker.left_marker, "");
-        clean_content = clean_content.replace(&opt.to_marker.right_marker, "");
+    ///
+    /// # Returns
+    /// * the ticket, read as far as possible, and the problems found in it; diagnostics carry `intext_position`
+    pub fn parse(marked_content_only: &str, opt: &ToParserOption, intext_position: Option<ToTicketPositionInfo>) -> (Self, Vec<ToParseDiagnostic>) {
+        let mut diagnostics: Vec<ToParseDiagnostic> = Vec::new();
 
+        let to_marker = &opt.to_marker;
+        // remove left and right markers if they exist; markers inside values are escaped
+        let mut clean_content = marked_content_only.trim();
+        clean_content = clean_content.strip_prefix(to_marker.left_marker.as_str()).unwrap_or(clean_content);
+        if let Some(without_right_marker) = clean_content.strip_suffix(to_marker.right_marker.as_str()) {
+            if !to_marker.ends_with_escape(without_right_marker) {
+                clean_content = without_right_marker;
+            }
+        }
 
-        // split strings with separator for once
-        let split_content = clean_content.split(&opt.to_marker.value_entry_separator);
+        // split strings with unescaped separators
+        let split_content = to_marker.split_unescaped(clean_content, &to_marker.value_entry_separator, usize::MAX);
         // iterate through the split strings
-        let mut to_ticket = ToTicket::default();
         // load position if exists
-        to_ticket.to_intext_option = intext_position;
+        let mut to_ticket = ToTicket {
+            to_intext_option: intext_position.clone(),
+            ..Default::default()
+        };
         for content in split_content {
             // split the string with separator for once, ignoring the value separator if it comes after the key separator
-            let mut split_content = content.splitn(2, &opt.to_marker.value_separator);
-            // get the key and value
-            let mut key = split_content.next().unwrap_or("").to_string();
+            let mut split_content = to_marker.split_unescaped(content, &to_marker.value_separator, 2).into_iter();
+            // get the key and value, without the whitespace printed around separators
+            let written_key = split_content.next().unwrap_or("").trim();
+            // a reserved key written after an escape character is a user value
+            let reserved_as_value = to_marker.strip_reserved_escape(written_key)
+                .map(|key| to_marker.unescape(key))
+                .filter(|key| opt.ticket_spec.field_of(key).is_some());
+            let mut key = reserved_as_value.clone().unwrap_or_else(|| to_marker.unescape(written_key));
             // if key is empty, continue
             // make sure the value is not empty, if it is, set it to empty string
-            let mut value = split_content.next().unwrap_or("").to_string();
+            let mut value = to_marker.unescape(split_content.next().unwrap_or("").trim());
 
             // this occurs when e.g. [[:value_with_no_key]]
             if key.is_empty() {
@@ -42,51 +62,88 @@ impl ToTicket {
                 }
             }
 
-            // check if the key is a reserved field, if so, assign it to the corresponding field
-            match key.as_ref() {
-                "id" => { to_ticket.ticket_id = value }
-                "updated" => {
-                    let naive_updated = NaiveDateTime::parse_from_str(&value, &opt.date_format).unwrap();
-                    to_ticket.to_updated = DateTime::<FixedOffset>::from_utc(naive_updated, FixedOffset::east(0));
+            // check if the key is a reserved field of the ticket spec, if so, assign it to the corresponding field
+            let field = if reserved_as_value.is_some() { None } else { opt.ticket_spec.field_of(&key) };
+            match field {
+                Some(ToTicketField::Id) => {
+                    if value.is_empty() {
+                        diagnostics.push(ToParseDiagnostic::warning("Ticket has an empty id".to_string(), intext_position.clone()).with_kind(ToDiagnosticKind::EmptyId));
+                    }
+                    to_ticket.ticket_id = value
+                }
+                Some(ToTicketField::Updated) => {
+                    // keep the default date when the written one cannot be read
+                    if let Some(updated) = read_date(&key, &value, opt, &intext_position, &mut diagnostics) {
+                        to_ticket.to_updated = updated;
+                    }
                 }
-                "store_id" => {
+                Some(ToTicketField::Created) => {
+                    to_ticket.to_created = read_date(&key, &value, opt, &intext_position, &mut diagnostics);
+                }
+                Some(ToTicketField::StoreUrl) => {
                     to_ticket.to_store_url = Some(value);
                 }
-                "store_info" => {
+                Some(ToTicketField::StoreInfo) => {
                     to_ticket.to_store_info = Some(value);
                 }
-                _ => {
+                Some(ToTicketField::Label) => {
+                    to_ticket.to_label = Some(value).filter(|label| !label.is_empty());
+                }
+                None => {
                     // if the key is not a reserved field, add it to the values
-                    to_ticket.values.insert(key.to_string(), value);
+                    to_ticket.add_value(key.to_string(), ToTicketValue::from_printed(&value));
                 }
             }
         }
-        to_ticket
+        (to_ticket, diagnostics)
     }
 
 
-    fn from_json(json: &str) -> Self {
-        serde_json::from_str(json).unwrap()
+    pub fn from_json(json: &str) -> Result<Self, ToErrors> {
+        serde_json::from_str(json).map_err(|e| ToErrors::ParseError(TextualObjectErrorMessage {
+            message: format!("Cannot read ticket from json: {}", e),
+            ..Default::default()
+        }))
     }
 }
 
 
+// read a date field written with the date format of the parser option, UTC if no offset is given
+fn read_date(key: &str, value: &str, opt: &ToParserOption, intext_position: &Option<ToTicketPositionInfo>, diagnostics: &mut Vec<ToParseDiagnostic>) -> Option<DateTime<FixedOffset>> {
+    match NaiveDateTime::parse_from_str(value, &opt.date_format) {
+        Ok(naive_date) => Some(Utc.fix().from_utc_datetime(&naive_date)),
+        Err(e) => {
+            diagnostics.push(ToParseDiagnostic::error(
+                format!("Cannot read {} date \"{}\" with format \"{}\": {}", key, value, opt.date_format, e),
+                intext_position.clone(),
+            ).with_kind(ToDiagnosticKind::MalformedDate));
+            None
+        }
+    }
+}
+
 // test create default TextualObjectTicket
 #[cfg(test)]
 mod tests {
     /*
 Parser tests
  */
-    use chrono::{Datelike, TimeZone, Utc};
+    use chrono::{Datelike, Offset, TimeZone, Utc};
+    use proptest::prelude::*;
 
+    use crate::to_parser::parser_diagnostic::ToDiagnosticSeverity;
     use crate::to_parser::parser_option::ToParserOption;
+    use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
+    use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+    use crate::to_ticket::to_ticket_spec::{ToTicketField, ToTicketSpec};
     use crate::to_ticket::to_ticket_struct::ToTicket;
+    use crate::to_ticket::to_ticket_value::ToTicketValue;
 
     #[test]
     fn test_parse() {
         let mark_content = "key1:value1|key2:value2";
         let p1 = ToParserOption::default();
-        let to_ticket = ToTicket::parse(mark_content, &p1, None);
+        let (to_ticket, _) = ToTicket::parse(mark_content, &p1, None);
         assert_eq!(to_ticket.values.len(), 2);
         assert_eq!(to_ticket.values.get("key1").unwrap(), "value1");
         assert_eq!(to_ticket.values.get("key2").unwrap(), "value2");
@@ -96,7 +153,7 @@ Parser tests
     fn test_parse_string_with_markers() {
         let opt = ToParserOption::default();
         let mark_content = format!("{}key1:value1|key2:value2{}", opt.to_marker.left_marker, opt.to_marker.right_marker);
-        let to_ticket = ToTicket::parse(&mark_content, &opt, None);
+        let (to_ticket, _) = ToTicket::parse(&mark_content, &opt, None);
         assert_eq!(to_ticket.values.len(), 2);
         assert_eq!(to_ticket.values.get("key1").unwrap(), "value1");
         assert_eq!(to_ticket.values.get("key2").unwrap(), "value2");
@@ -108,7 +165,7 @@ Parser tests
     fn test_parse_with_meta_data() {
         let opt = ToParserOption::default();
         let mark_content = format!("{}id:test_id|key1:value1|key2:value2|updated:2018-01-01 00:00:00|store_info:store_info|store_id:store_id{}", opt.to_marker.left_marker, opt.to_marker.right_marker);
-        let to_ticket = ToTicket::parse(&mark_content, &opt, None);
+        let (to_ticket, _) = ToTicket::parse(&mark_content, &opt, None);
         assert_eq!(to_ticket.ticket_id, "test_id".to_string());
         assert_eq!(to_ticket.values.len(), 2);
         assert_eq!(to_ticket.values.get("key1").unwrap(), "value1");
@@ -122,7 +179,7 @@ Parser tests
     fn test_parse_with_missing_values() {
         let opt = ToParserOption::default();
         let mark_content = format!("{}id:test_id|key1:|key2|:value1|updated:2018-01-01 00:00:00|store_info:store_info{}", opt.to_marker.left_marker, opt.to_marker.right_marker);
-        let to_ticket = ToTicket::parse(&mark_content, &opt, None);
+        let (to_ticket, _) = ToTicket::parse(&mark_content, &opt, None);
         assert_eq!(to_ticket.ticket_id, "test_id".to_string());
         assert_eq!(to_ticket.values.len(), 3);
         assert_eq!(to_ticket.values.get("key1").unwrap(), "");
@@ -146,7 +203,7 @@ Parser tests
                 ["key2", "value2"]
             ]
         }"#;
-        let to_ticket = ToTicket::from_json(json);
+        let to_ticket = ToTicket::from_json(json).unwrap();
         assert_eq!(to_ticket.ticket_id, "12345".to_string());
         assert_eq!(to_ticket.values.len(), 2);
         assert_eq!(to_ticket.values.get("key1").unwrap(), "value1");
@@ -155,4 +212,245 @@ Parser tests
         assert_eq!(to_ticket.to_store_url, Some("store_url_value".to_string()));
         assert_eq!(to_ticket.to_store_info, Some("store_info_value".to_string()));
     }
-}
\ No newline at end of file
+
+    // test malformed dates do not panic and are reported with the position
+    #[test]
+    fn test_parse_malformed_date() {
+        let opt = ToParserOption::default();
+        let position = ToTicketPositionInfo {
+            line: 3,
+            column: 5,
+            length: 30,
+            raw_text: "[[id:abc|updated:not a date]]".to_string(),
+            ..Default::default()
+        };
+        let (to_ticket, diagnostics) = ToTicket::parse("id:abc|updated:not a date|key1:value1", &opt, Some(position.clone()));
+        assert_eq!(to_ticket.ticket_id, "abc");
+        assert_eq!(to_ticket.values.get("key1").unwrap(), "value1");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, ToDiagnosticSeverity::Error);
+        assert_eq!(diagnostics[0].position, Some(position));
+    }
+
+    // test empty id is a warning
+    #[test]
+    fn test_parse_empty_id() {
+        let opt = ToParserOption::default();
+        let (_, diagnostics) = ToTicket::parse("[[id:|key1:value1]]", &opt, None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, ToDiagnosticSeverity::Warning);
+    }
+
+    // test reading from invalid json
+    #[test]
+    fn test_from_invalid_json() {
+        assert!(ToTicket::from_json("{ not json").is_err());
+    }
+
+    // test parsing a printed ticket with spaces around separators
+    #[test]
+    fn test_parse_printed_ticket() {
+        let opt = ToParserOption::default();
+        let mut ticket = ToTicket {
+            ticket_id: "abc".to_string(),
+            to_store_url: Some("/path/to/store.db".to_string()),
+            to_store_info: Some("My Store".to_string()),
+            ..Default::default()
+        };
+        ticket.values.insert("key1".to_string(), "value1".into());
+        ticket.values.insert("title".to_string(), "Discipline: the Birth".into());
+        let printed = ticket.print(None);
+        let (parsed, _) = ToTicket::parse(&printed, &opt, None);
+        assert_eq!(parsed.values.get("key1").unwrap(), "value1");
+        assert_eq!(parsed.values.get("title").unwrap(), "Discipline: the Birth");
+        assert_eq!(parsed.to_store_url, Some("/path/to/store.db".to_string()));
+        assert_eq!(parsed.to_store_info, Some("My Store".to_string()));
+        assert_eq!(parsed, ticket);
+    }
+
+    // test typed values and repeated keys
+    #[test]
+    fn test_parse_typed_and_repeated_values() {
+        let opt = ToParserOption::default();
+        let (ticket, _) = ToTicket::parse("[[id:abc|tag:a|pages:12|draft:true|read:2020-01-31|tag:b|code:\"12\"]]", &opt, None);
+        assert_eq!(ticket.values.get("tag").unwrap(), &ToTicketValue::List(vec!["a".into(), "b".into()]));
+        assert_eq!(ticket.values.get("pages").unwrap(), &ToTicketValue::Number(12.into()));
+        assert_eq!(ticket.values.get("draft").unwrap(), &ToTicketValue::Bool(true));
+        assert_eq!(ticket.values.get("read").unwrap(), &ToTicketValue::Date(chrono::NaiveDate::from_ymd_opt(2020, 1, 31).unwrap()));
+        assert_eq!(ticket.values.get("code").unwrap(), &ToTicketValue::from("12"));
+
+        let printed = ticket.print(None);
+        assert!(printed.starts_with("[[id: abc | tag: a | tag: b | pages: 12 | draft: true | read: 2020-01-31 | code: \"12\" |"));
+        let (parsed, _) = ToTicket::parse(&printed, &opt, None);
+        assert_eq!(parsed, ticket);
+        let from_json = ToTicket::from_json(&ticket.to_json()).unwrap();
+        assert_eq!(from_json.values, ticket.values);
+
+        // a list of one item is printed as a single entry, and read back as the item
+        let mut ticket = ToTicket::default();
+        ticket.values.insert("tag".to_string(), ToTicketValue::List(vec!["a".into()]));
+        let (parsed, _) = ToTicket::parse(&ticket.print(None), &opt, None);
+        assert_eq!(parsed.values.get("tag").unwrap(), &ToTicketValue::from("a"));
+    }
+
+    // with a prefix, user values may use the names of reserved fields
+    #[test]
+    fn test_parse_print_with_prefixed_spec() {
+        let opt = ToParserOption {
+            ticket_spec: ToTicketSpec::with_prefix("@"),
+            ..Default::default()
+        };
+        let (ticket, diagnostics) = ToTicket::parse("[[id:abc|updated:yesterday|@updated:2018-01-01 10:00:00|@created:2017-01-01 10:00:00|@store_id:store]]", &opt, None);
+        assert!(diagnostics.is_empty());
+        assert_eq!(ticket.values.get("updated").unwrap(), "yesterday");
+        assert_eq!(ticket.to_updated, Utc.fix().with_ymd_and_hms(2018, 1, 1, 10, 0, 0).unwrap());
+        assert_eq!(ticket.to_created, Some(Utc.fix().with_ymd_and_hms(2017, 1, 1, 10, 0, 0).unwrap()));
+        assert_eq!(ticket.to_store_url.as_deref(), Some("store"));
+
+        let printed = ticket.print(Some(ToTicketPrintOption {
+            ticket_spec: opt.ticket_spec.clone(),
+            ..Default::default()
+        }));
+        assert_eq!(printed, "[[id: abc | updated: yesterday | @updated: 2018-01-01 10:00:00 | @created: 2017-01-01 10:00:00 | @store_id: store]]");
+        let (parsed, _) = ToTicket::parse(&printed, &opt, None);
+        assert_eq!(parsed, ticket);
+    }
+
+    // created dates are read like updated dates
+    #[test]
+    fn test_parse_created() {
+        let opt = ToParserOption::default();
+        let (ticket, diagnostics) = ToTicket::parse("[[id:abc|created:2017-01-01 10:00:00]]", &opt, None);
+        assert!(diagnostics.is_empty());
+        assert_eq!(ticket.to_created, Some(Utc.fix().with_ymd_and_hms(2017, 1, 1, 10, 0, 0).unwrap()));
+        assert!(ticket.values.is_empty());
+
+        let (ticket, diagnostics) = ToTicket::parse("[[id:abc|created:someday]]", &opt, None);
+        assert_eq!(ticket.to_created, None);
+        assert_eq!(diagnostics[0].severity, ToDiagnosticSeverity::Error);
+
+        // a version 1 spec has no created field
+        let opt = ToParserOption {
+            ticket_spec: ToTicketSpec { version: 1, reserved_prefix: String::new(), reserve_label: false },
+            ..Default::default()
+        };
+        let (ticket, _) = ToTicket::parse("[[id:abc|created:2017-01-01 10:00:00]]", &opt, None);
+        assert_eq!(ticket.to_created, None);
+        assert_eq!(ticket.values.get("created").unwrap(), "2017-01-01 10:00:00");
+    }
+
+    // user values named like reserved fields are escaped, so that they are read back as values
+    #[test]
+    fn test_round_trip_keeps_reserved_keys_in_values() {
+        let opt = ToParserOption::default();
+        let mut ticket = ToTicket::default();
+        ticket.values.insert("id".to_string(), "wrong_id".into());
+        ticket.values.insert("updated".to_string(), "yesterday".into());
+        ticket.values.insert("key1".to_string(), "value1".into());
+        let printed = ticket.print(None);
+        assert!(printed.contains("| \\id: wrong_id | \\updated: yesterday | key1: value1 |"));
+        let (parsed, diagnostics) = ToTicket::parse(&printed, &opt, None);
+        assert!(diagnostics.is_empty());
+        assert_eq!(parsed, ticket);
+
+        // an escaped escape character starts an ordinary key
+        let (parsed, _) = ToTicket::parse("[[id:abc|\\\\id:1]]", &opt, None);
+        assert_eq!(parsed.ticket_id, "abc");
+        assert_eq!(parsed.values.get("\\id").unwrap(), &ToTicketValue::Number(1.into()));
+    }
+
+    // keys of user values, often named like reserved fields
+    fn value_key() -> impl Strategy<Value=String> {
+        prop_oneof![
+            "[a-z][a-z0-9_]{0,8}",
+            proptest::sample::select(vec!["id", "updated", "created", "store_id", "store_info", "label", "@updated", "@label"]).prop_map(|key| key.to_string()),
+        ]
+        // keys of the private meta-data are never printed
+        .prop_filter("private meta-data key", |key| !matches!(key.as_str(), "to_updated" | "to_store_id" | "to_store_info" | "to_marker"))
+    }
+
+    // specs with and without a reserved prefix and label
+    fn ticket_spec() -> impl Strategy<Value=ToTicketSpec> {
+        prop_oneof![Just(ToTicketSpec::default()), Just(ToTicketSpec::with_label()), Just(ToTicketSpec::with_prefix("@"))]
+    }
+
+    // tickets with normalized content: trimmed keys and values, no markers or separators in values
+    fn normalized_ticket() -> impl Strategy<Value=ToTicket> {
+        (
+            "[a-zA-Z0-9_-]{1,10}",
+            proptest::collection::vec((value_key(), "[^|\\[\\]\r\n]{0,20}"), 0..6),
+            0i64..4_102_444_800,
+            proptest::option::of(0i64..4_102_444_800),
+            proptest::option::of("[a-zA-Z0-9_./-]{1,20}"),
+            proptest::option::of("[a-zA-Z0-9 _-]{1,20}"),
+            proptest::option::of("[a-zA-Z0-9 ,&.-]{1,30}"),
+        ).prop_map(|(ticket_id, values, updated, created, store_url, store_info, label)| {
+            let mut ticket = ToTicket {
+                ticket_id,
+                to_updated: Utc.fix().timestamp_opt(updated, 0).unwrap(),
+                to_created: created.map(|created| Utc.fix().timestamp_opt(created, 0).unwrap()),
+                to_store_url: store_url,
+                to_store_info: store_info.map(|info| info.trim().to_string()).filter(|info| !info.is_empty()),
+                to_label: label.map(|label| label.trim().to_string()).filter(|label| !label.is_empty()),
+                ..Default::default()
+            };
+            for (key, value) in values {
+                ticket.values.insert(key, value.trim().into());
+            }
+            ticket
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_parse_print_round_trip(mut ticket in normalized_ticket(), spec in ticket_spec()) {
+            // the label is only printed where the spec reserves it
+            if !spec.fields().contains(&ToTicketField::Label) {
+                ticket.to_label = None;
+            }
+            let opt = ToParserOption {
+                ticket_spec: spec.clone(),
+                ..Default::default()
+            };
+            let printed = ticket.print(Some(ToTicketPrintOption {
+                include_label: true,
+                ticket_spec: spec,
+                ..Default::default()
+            }));
+            let (parsed, _) = ToTicket::parse(&printed, &opt, None);
+            // the equality of tickets ignores the label
+            prop_assert_eq!(&parsed.to_label, &ticket.to_label);
+            prop_assert_eq!(parsed, ticket);
+        }
+
+        #[test]
+        fn prop_print_is_stable_after_one_round_trip(mut ticket in normalized_ticket(), padding in "[ \t]{0,3}") {
+            // untrimmed content is normalized by the first round trip and stays the same afterwards
+            let opt = ToParserOption::default();
+            ticket.to_label = None;
+            let mut padded = ticket.clone();
+            padded.values = ticket.values.iter().map(|(key, value)| (format!("{}{}", padding, key), format!("{}{}{}", padding, value, padding).into())).collect();
+            let (once, _) = ToTicket::parse(&padded.print(None), &opt, None);
+            let (twice, _) = ToTicket::parse(&once.print(None), &opt, None);
+            prop_assert_eq!(&once, &ticket);
+            prop_assert_eq!(once.print(None), twice.print(None));
+        }
+    }
+
+    // any value, including separators, markers and line breaks, survives printing
+    proptest! {
+        #[test]
+        fn prop_escaped_values_round_trip(key in "[^\\s]{1,10}", value in "(\\PC|\n|\t){0,30}") {
+            let opt = ToParserOption::default();
+            let mut ticket = ToTicket::default();
+            let key = key.trim().to_string();
+            // keys of private meta-data are not printed as values
+            prop_assume!(!key.is_empty() && !key.starts_with("to_"));
+            ticket.values.insert(key.clone(), value.trim().into());
+            let printed = ticket.print(None);
+            prop_assert!(!printed.contains('\n'));
+            let (parsed, _) = ToTicket::parse(&printed, &opt, None);
+            prop_assert_eq!(parsed, ticket);
+        }
+    }
+}
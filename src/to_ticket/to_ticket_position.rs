@@ -1,22 +1,197 @@
-use regex::Captures;
 use serde::{Deserialize, Serialize};
 
-/// struct for recognition the regex match position: line, column, length of the ticket in the original text
-#[derive(Serialize, Deserialize, Clone)]
+/// struct for recognition the regex match position: line, column, length of the ticket in the original text.
+/// Lines and columns count from 0; `column` and `length` are in bytes; the end is exclusive.
+/// Editors count columns differently, e.g. VS Code in UTF-16 code units, others in chars, so those are given as well.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
 pub struct ToTicketPositionInfo {
     pub line: usize,
     pub column: usize,
     pub length: usize,
     pub raw_text: String,
+    // line and column right after the ticket, differ from the start line when the ticket is wrapped
+    #[serde(default)]
+    pub end_line: usize,
+    #[serde(default)]
+    pub end_column: usize,
+
+    // offsets from the start of the text
+    #[serde(default)]
+    pub byte_offset: usize,
+    #[serde(default)]
+    pub end_byte_offset: usize,
+    #[serde(default)]
+    pub char_offset: usize,
+    #[serde(default)]
+    pub end_char_offset: usize,
+
+    // columns inside the line in chars and in UTF-16 code units
+    #[serde(default)]
+    pub char_column: usize,
+    #[serde(default)]
+    pub end_char_column: usize,
+    #[serde(default)]
+    pub utf16_column: usize,
+    #[serde(default)]
+    pub end_utf16_column: usize,
 }
 
 impl ToTicketPositionInfo {
-    pub fn from_match(m: &Captures, line: usize) -> Self {
+    /// position of the byte range `start..end` of `text`; `line_starts` is the result of `line_starts(text)`.
+    /// Only the lines of the range are read, so the positions of many tickets of a long text are cheap
+    pub fn from_span(text: &str, start: usize, end: usize, line_starts: &ToLineStarts) -> Self {
+        let (line, column) = line_starts.line_and_column(start);
+        let (end_line, end_column) = line_starts.line_and_column(end);
+        let line_text = &text[line_starts.bytes[line]..start];
+        let end_line_text = &text[line_starts.bytes[end_line]..end];
+        let char_column = line_text.chars().count();
+        let end_char_column = end_line_text.chars().count();
         ToTicketPositionInfo {
             line,
-            column: m.get(0).unwrap().start(),
-            length: m.get(0).unwrap().end() - m.get(0).unwrap().start(),
-            raw_text: m.get(0).unwrap().as_str().to_string(),
+            column,
+            length: end - start,
+            raw_text: text[start..end].to_string(),
+            end_line,
+            end_column,
+            byte_offset: start,
+            end_byte_offset: end,
+            char_offset: line_starts.chars[line] + char_column,
+            end_char_offset: line_starts.chars[end_line] + end_char_column,
+            char_column,
+            end_char_column,
+            utf16_column: line_text.encode_utf16().count(),
+            end_utf16_column: end_line_text.encode_utf16().count(),
+        }
+    }
+
+    /// the same position in a document where the scanned text starts at the given line and offsets.
+    /// The scanned text must start at the beginning of a line, so columns do not change
+    pub fn shifted(mut self, line: usize, byte_offset: usize, char_offset: usize) -> Self {
+        self.line += line;
+        self.end_line += line;
+        self.byte_offset += byte_offset;
+        self.end_byte_offset += byte_offset;
+        self.char_offset += char_offset;
+        self.end_char_offset += char_offset;
+        self
+    }
+}
+
+/// Offsets at which each line of a text starts, in bytes and in chars.
+/// They are kept up to date as text is appended to the text or removed from its start, e.g. by a reader
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToLineStarts {
+    pub bytes: Vec<usize>,
+    pub chars: Vec<usize>,
+    // length of the text in bytes and chars
+    len: usize,
+    char_len: usize,
+}
+
+impl Default for ToLineStarts {
+    fn default() -> Self {
+        ToLineStarts {
+            bytes: vec![0],
+            chars: vec![0],
+            len: 0,
+            char_len: 0,
+        }
+    }
+}
+
+impl ToLineStarts {
+    /// line and byte column of a byte offset
+    pub fn line_and_column(&self, offset: usize) -> (usize, usize) {
+        let line = self.bytes.partition_point(|start| *start <= offset) - 1;
+        (line, offset - self.bytes[line])
+    }
+
+    /// record the lines of text appended to the text
+    pub fn push_str(&mut self, appended: &str) {
+        for c in appended.chars() {
+            self.len += c.len_utf8();
+            self.char_len += 1;
+            if c == '\n' {
+                self.bytes.push(self.len);
+                self.chars.push(self.char_len);
+            }
         }
     }
-}
\ No newline at end of file
+
+    /// forget the first `until` bytes of the text, `until_chars` chars, so that the text starts at `until`
+    pub fn drain(&mut self, until: usize, until_chars: usize) {
+        let (line, _) = self.line_and_column(until);
+        self.bytes.drain(..line);
+        self.chars.drain(..line);
+        self.bytes[0] = until;
+        self.chars[0] = until_chars;
+        self.bytes.iter_mut().for_each(|start| *start -= until);
+        self.chars.iter_mut().for_each(|start| *start -= until_chars);
+        self.len -= until;
+        self.char_len -= until_chars;
+    }
+}
+
+/// offsets at which each line of the text starts
+pub fn line_starts(text: &str) -> ToLineStarts {
+    let mut starts = ToLineStarts::default();
+    starts.push_str(text);
+    starts
+}
+
+// test
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_span_across_lines() {
+        let text = "ab\n[[id:\n1]] c";
+        let starts = line_starts(text);
+        assert_eq!(starts.bytes, vec![0, 3, 9]);
+        let position = ToTicketPositionInfo::from_span(text, 3, 12, &starts);
+        assert_eq!(position.line, 1);
+        assert_eq!(position.column, 0);
+        assert_eq!(position.end_line, 2);
+        assert_eq!(position.end_column, 3);
+        assert_eq!(position.raw_text, "[[id:\n1]]");
+        assert_eq!(position.length, 9);
+        assert_eq!(position.byte_offset, 3);
+        assert_eq!(position.end_byte_offset, 12);
+    }
+
+    #[test]
+    fn test_from_span_with_non_ascii_text() {
+        // 2 CJK chars of 3 bytes, an accented char of 2 bytes and an emoji of 4 bytes and 2 UTF-16 units
+        let text = "引文\né😀 [[id:1]]";
+        let starts = line_starts(text);
+        let start = text.find("[[").unwrap();
+        let end = text.len();
+        let position = ToTicketPositionInfo::from_span(text, start, end, &starts);
+        assert_eq!(position.line, 1);
+        assert_eq!(position.column, 7);
+        assert_eq!(position.char_column, 3);
+        assert_eq!(position.utf16_column, 4);
+        assert_eq!(position.end_char_column, 11);
+        assert_eq!(position.end_utf16_column, 12);
+        assert_eq!(position.byte_offset, 14);
+        assert_eq!(position.char_offset, 6);
+        assert_eq!(position.end_char_offset, 14);
+        assert_eq!(starts.chars, vec![0, 3]);
+    }
+
+    #[test]
+    fn test_line_starts_kept_up_to_date() {
+        let text = "引文\né😀 [[id:1]]\nlast";
+        let mut starts = line_starts("");
+        for chunk in ["引", "文\né😀", " [[id:1]]\nla", "st"] {
+            starts.push_str(chunk);
+        }
+        assert_eq!(starts, line_starts(text));
+
+        // from inside the second line
+        let until = text.find("[[").unwrap();
+        starts.drain(until, text[..until].chars().count());
+        assert_eq!(starts, line_starts(&text[until..]));
+    }
+}
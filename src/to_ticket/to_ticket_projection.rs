@@ -0,0 +1,187 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+use crate::to_ticket::to_ticket_value::ToTicketValue;
+
+/// A field of the JSON of a TO projected into a ticket
+#[derive(Deserialize, Serialize, ToSchema, Clone, Debug, PartialEq)]
+pub struct ToProjectionField {
+    // dot separated path in the JSON, e.g. `author.name`; array items are selected by index, e.g. `authors.0`
+    pub path: String,
+    // key of the value in the ticket, the path when None
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+impl ToProjectionField {
+    pub fn new(path: &str) -> Self {
+        ToProjectionField {
+            path: path.to_string(),
+            key: None,
+        }
+    }
+
+    /// project the field under another key
+    pub fn with_key(path: &str, key: &str) -> Self {
+        ToProjectionField {
+            path: path.to_string(),
+            key: Some(key.to_string()),
+        }
+    }
+}
+
+/// Projection of the JSON of a TO into the values of a ticket.
+/// Nested objects are flattened into dotted keys, e.g. `{"author": {"name": "Ada"}}` gives `author.name: Ada`;
+/// arrays become lists and null values are left out.
+#[derive(Deserialize, Serialize, ToSchema, Clone, Debug, Default, PartialEq)]
+pub struct ToTicketProjection {
+    // fields to project, in order; every field when empty
+    #[serde(default)]
+    pub fields: Vec<ToProjectionField>,
+}
+
+impl ToTicketProjection {
+    /// projection of the given paths, under their own path
+    pub fn of_paths(paths: &[&str]) -> Self {
+        ToTicketProjection {
+            fields: paths.iter().map(|path| ToProjectionField::new(path)).collect(),
+        }
+    }
+
+    /// ticket values of a JSON; a payload that is not an object is projected under the key `value`
+    pub fn project(&self, json: &Value) -> IndexMap<String, ToTicketValue> {
+        let mut values = IndexMap::new();
+        if self.fields.is_empty() {
+            match json {
+                Value::Object(_) => flatten("", json, &mut values),
+                other => flatten("value", other, &mut values),
+            }
+            return values;
+        }
+        for field in &self.fields {
+            if let Some(selected) = select(json, &field.path) {
+                flatten(field.key.as_deref().unwrap_or(&field.path), selected, &mut values);
+            }
+        }
+        values
+    }
+}
+
+// value at a dot separated path, None if any part of the path is missing
+fn select<'a>(json: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').filter(|segment| !segment.is_empty()).try_fold(json, |value, segment| match value {
+        Value::Object(map) => map.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => None,
+    })
+}
+
+// add the value under the key, nested objects under dotted keys
+fn flatten(key: &str, value: &Value, values: &mut IndexMap<String, ToTicketValue>) {
+    match value {
+        Value::Object(map) => {
+            for (child_key, child) in map {
+                let child_key = if key.is_empty() { child_key.clone() } else { format!("{}.{}", key, child_key) };
+                flatten(&child_key, child, values);
+            }
+        }
+        other => {
+            if let Some(readable) = readable(other) {
+                if !key.is_empty() {
+                    values.insert(key.to_string(), readable);
+                }
+            }
+        }
+    }
+}
+
+// ticket value of a JSON value, None for null and empty arrays
+fn readable(value: &Value) -> Option<ToTicketValue> {
+    match value {
+        Value::Null => None,
+        Value::Bool(value) => Some(ToTicketValue::Bool(*value)),
+        Value::Number(value) => Some(ToTicketValue::Number(value.clone())),
+        Value::String(value) => Some(ToTicketValue::String(value.clone())),
+        Value::Array(items) => {
+            let items: Vec<ToTicketValue> = items.iter().filter_map(readable).collect();
+            if items.is_empty() {
+                None
+            } else {
+                Some(ToTicketValue::List(items))
+            }
+        }
+        // objects within arrays cannot be flattened into keys, so they are written inline, e.g. `name: Ada; born: 1815`
+        Value::Object(map) => Some(ToTicketValue::String(inline(map))),
+    }
+}
+
+// entries sorted by key, so that the value does not depend on the order of the JSON object
+fn inline(map: &Map<String, Value>) -> String {
+    let mut entries: Vec<(&String, ToTicketValue)> = map.iter()
+        .filter_map(|(key, value)| readable(value).map(|value| (key, value)))
+        .collect();
+    entries.sort_by_key(|(key, _)| *key);
+    let entries: Vec<String> = entries.into_iter().map(|(key, value)| format!("{}: {}", key, value)).collect();
+    entries.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sample() -> Value {
+        json!({
+            "title": "Discipline and Punish",
+            "year": 1975,
+            "author": {"name": "Foucault", "born": {"year": 1926}},
+            "tags": ["prison", "power"],
+            "editions": [{"year": 1975, "lang": "fr"}, {"year": 1977, "lang": null}],
+            "note": null,
+            "empty": []
+        })
+    }
+
+    #[test]
+    fn test_project_all() {
+        let values = ToTicketProjection::default().project(&sample());
+        // in the order of the JSON object, which depends on the features of serde_json
+        let mut keys: Vec<&str> = values.keys().map(|key| key.as_str()).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec!["author.born.year", "author.name", "editions", "tags", "title", "year"]);
+        assert_eq!(values["title"], ToTicketValue::from("Discipline and Punish"));
+        assert_eq!(values["year"], ToTicketValue::Number(1975.into()));
+        assert_eq!(values["tags"], ToTicketValue::List(vec!["prison".into(), "power".into()]));
+        assert_eq!(values["editions"].to_string(), "lang: fr; year: 1975, year: 1977");
+    }
+
+    #[test]
+    fn test_project_fields() {
+        let projection = ToTicketProjection {
+            fields: vec![
+                ToProjectionField::new("title"),
+                ToProjectionField::with_key("author.name", "author"),
+                ToProjectionField::new("author.born"),
+                ToProjectionField::with_key("tags.1", "tag"),
+                ToProjectionField::new("missing.path"),
+            ],
+        };
+        let values = projection.project(&sample());
+        let keys: Vec<&str> = values.keys().map(|key| key.as_str()).collect();
+        assert_eq!(keys, vec!["title", "author", "author.born.year", "tag"]);
+        assert_eq!(values["author"], ToTicketValue::from("Foucault"));
+        assert_eq!(values["tag"], ToTicketValue::from("power"));
+    }
+
+    #[test]
+    fn test_project_non_object() {
+        let projection = ToTicketProjection::default();
+        assert!(projection.project(&Value::Null).is_empty());
+        assert_eq!(projection.project(&json!("text"))["value"], ToTicketValue::from("text"));
+        assert_eq!(projection.project(&json!([1, 2]))["value"], ToTicketValue::List(vec![ToTicketValue::Number(1.into()), ToTicketValue::Number(2.into())]));
+        assert!(ToTicketProjection::of_paths(&["title"]).project(&json!(12)).is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+/// Reserved fields of a ticket, read into the meta-data of a ToTicket rather than its values
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToTicketField {
+    Id,
+    Updated,
+    Created,
+    // written as store_id for backward compatibility
+    StoreUrl,
+    StoreInfo,
+    // display label, not authoritative: it is never used to find the TO and can be refreshed from it
+    Label,
+}
+
+/// Versioned specification of the reserved fields of a ticket, shared by the printer and the parser.
+///
+/// * version 1: `id`, `updated`, `store_id` and `store_info`
+/// * version 2: adds `created`, and an optional prefix for reserved keys other than `id`,
+///   e.g. with the prefix `@` a ticket is written `[[id: abc | @updated: ... | updated: a user value]]`
+/// * version 3: adds `label`, e.g. `[[id: abc | label: Foucault 1975, Discipline and Punish]]`;
+///   `label` is a common user key, so it is reserved only when requested, or under a prefix as e.g. `@label`
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ToTicketSpec {
+    pub version: u32,
+    // prefix of every reserved key but id; empty by default, so that tickets written before prefixes are read the same
+    #[serde(default)]
+    pub reserved_prefix: String,
+    // whether `label` is reserved without a prefix
+    #[serde(default)]
+    pub reserve_label: bool,
+}
+
+impl Default for ToTicketSpec {
+    fn default() -> Self {
+        ToTicketSpec {
+            version: ToTicketSpec::CURRENT_VERSION,
+            reserved_prefix: String::new(),
+            reserve_label: false,
+        }
+    }
+}
+
+impl ToTicketSpec {
+    pub const CURRENT_VERSION: u32 = 3;
+
+    /// current spec with a prefix for reserved keys
+    pub fn with_prefix(prefix: &str) -> Self {
+        ToTicketSpec {
+            reserved_prefix: prefix.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// current spec with `label` reserved, for reading and printing display labels
+    pub fn with_label() -> Self {
+        ToTicketSpec {
+            reserve_label: true,
+            ..Default::default()
+        }
+    }
+
+    /// reserved fields of this version, in printing order
+    pub fn fields(&self) -> Vec<ToTicketField> {
+        let mut fields = vec![ToTicketField::Id, ToTicketField::Updated];
+        if self.version >= 2 {
+            fields.push(ToTicketField::Created);
+        }
+        fields.push(ToTicketField::StoreInfo);
+        fields.push(ToTicketField::StoreUrl);
+        if self.version >= 3 && (self.reserve_label || !self.reserved_prefix.is_empty()) {
+            fields.push(ToTicketField::Label);
+        }
+        fields
+    }
+
+    /// key of a reserved field in a ticket
+    pub fn key(&self, field: ToTicketField) -> String {
+        let name = match field {
+            // the id is never prefixed, so that minimal tickets read the same in every version
+            ToTicketField::Id => return "id".to_string(),
+            ToTicketField::Updated => "updated",
+            ToTicketField::Created => "created",
+            ToTicketField::StoreUrl => "store_id",
+            ToTicketField::StoreInfo => "store_info",
+            ToTicketField::Label => "label",
+        };
+        // version 1 has no prefix
+        if self.version >= 2 {
+            format!("{}{}", self.reserved_prefix, name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    /// reserved field written with this key, None for a user value
+    pub fn field_of(&self, key: &str) -> Option<ToTicketField> {
+        self.fields().into_iter().find(|field| self.key(*field) == key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys() {
+        let spec = ToTicketSpec::default();
+        assert_eq!(spec.field_of("store_id"), Some(ToTicketField::StoreUrl));
+        assert_eq!(spec.field_of("created"), Some(ToTicketField::Created));
+        assert_eq!(spec.field_of("title"), None);
+        // label is a user value unless it is requested
+        assert_eq!(spec.field_of("label"), None);
+        assert_eq!(ToTicketSpec::with_label().field_of("label"), Some(ToTicketField::Label));
+
+        let version_1 = ToTicketSpec {
+            version: 1,
+            reserved_prefix: "@".to_string(),
+            reserve_label: true,
+        };
+        assert_eq!(version_1.field_of("created"), None);
+        assert_eq!(version_1.key(ToTicketField::Updated), "updated");
+
+        let version_2 = ToTicketSpec {
+            version: 2,
+            reserved_prefix: String::new(),
+            reserve_label: true,
+        };
+        assert_eq!(version_2.field_of("label"), None);
+        assert_eq!(version_2.field_of("created"), Some(ToTicketField::Created));
+    }
+
+    #[test]
+    fn test_prefix() {
+        let spec = ToTicketSpec::with_prefix("@");
+        assert_eq!(spec.key(ToTicketField::Id), "id");
+        assert_eq!(spec.key(ToTicketField::Updated), "@updated");
+        assert_eq!(spec.field_of("@store_info"), Some(ToTicketField::StoreInfo));
+        assert_eq!(spec.field_of("updated"), None);
+        // under a prefix the label cannot be mistaken for a user value
+        assert_eq!(spec.field_of("@label"), Some(ToTicketField::Label));
+        assert_eq!(spec.field_of("label"), None);
+    }
+}
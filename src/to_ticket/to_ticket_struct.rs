@@ -1,21 +1,23 @@
-use chrono::{DateTime, FixedOffset, Local};
+use chrono::{DateTime, FixedOffset, Local, Offset, Timelike, Utc};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 
-use crate::to_ticket::to_ticket_marker::ToMarker;
+use crate::to_ticket::to_ticket_marker::{ToMarker, ToMarkerDialect};
 use crate::to_ticket::to_ticket_position::ToTicketPositionInfo;
+use crate::to_ticket::to_ticket_value::ToTicketValue;
 use crate::utils::id_generator::generate_id;
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ToTicket {
     // unique ID in the local storage
     pub id: String,
     // unique ticket Id in the local storage
     pub ticket_id: String,
     // values: indexMap of keys and values; uses indexMap rather than HashMap because IndexMap preserves the insertion orderj
+    // a key repeated in the ticket holds a list, see add_value
     #[serde(with = "indexmap::serde_seq")]
-    pub values: IndexMap<String, String>,
+    pub values: IndexMap<String, ToTicketValue>,
     /*
     Public meta-data: the fields below are reserved for meta-data.
     In output, they are written with a PREFIX;
@@ -23,12 +25,18 @@ pub struct ToTicket {
     // updated date field: Chrono::DateTime
     #[serde(default)]
     pub to_updated: DateTime<FixedOffset>,
+    // created date field, only known for tickets printed from a TO or read from a ticket with a created date
+    #[serde(default)]
+    pub to_created: Option<DateTime<FixedOffset>>,
     // redable notes on storage location of the referenced TO
     #[serde(default)]
     pub to_store_url: Option<String>,
     // Optional unique ID of the storage field
     #[serde(default)]
     pub to_store_info: Option<String>,
+    // readable label of the referenced TO, e.g. "Foucault 1975, Discipline and Punish"; derived from the TO, so never used to find it
+    #[serde(default)]
+    pub to_label: Option<String>,
 
     /*
     Private meta-data, not be printed
@@ -37,6 +45,9 @@ pub struct ToTicket {
     pub to_marker: ToMarker,
     #[serde(default)]
     pub to_intext_option: Option<ToTicketPositionInfo>,
+    // how the ticket was written in the text it was scanned from
+    #[serde(default)]
+    pub to_dialect: ToMarkerDialect,
 
 }
 
@@ -47,18 +58,49 @@ impl Default for ToTicket {
             id: String::new(),
             ticket_id: generate_id(),
             values: IndexMap::new(),
-            to_updated: Local::now().with_timezone(&FixedOffset::east(0)),
+            // tickets print dates to the second
+            to_updated: Local::now().with_timezone(&Utc.fix()).with_nanosecond(0).unwrap(),
+            to_created: None,
             to_store_url: None,
             to_store_info: None,
+            to_label: None,
             to_marker: ToMarker::default(),
             to_intext_option: None,
+            to_dialect: ToMarkerDialect::default(),
         }
     }
 
 }
 
+impl ToTicket {
+    /// add a value; adding to a key that already has a value makes a list of both, as for `tag: a | tag: b`
+    pub fn add_value(&mut self, key: String, value: ToTicketValue) {
+        match self.values.get_mut(&key) {
+            Some(ToTicketValue::List(items)) => items.push(value),
+            Some(existing) => {
+                let first = std::mem::replace(existing, ToTicketValue::List(Vec::new()));
+                *existing = ToTicketValue::List(vec![first, value]);
+            }
+            None => {
+                self.values.insert(key, value);
+            }
+        }
+    }
+}
 
-
+/// Two tickets are equal when they carry the same printed content: ticket id, values and public meta-data.
+/// The local id, the label, which is derived from the TO, and private meta-data, i.e. the marker and the position in a text, are ignored;
+/// an empty store url or store info equals a missing one since neither is printed.
+impl PartialEq for ToTicket {
+    fn eq(&self, other: &Self) -> bool {
+        self.ticket_id == other.ticket_id
+            && self.values == other.values
+            && self.to_updated == other.to_updated
+            && self.to_created == other.to_created
+            && self.to_store_url.as_deref().unwrap_or("") == other.to_store_url.as_deref().unwrap_or("")
+            && self.to_store_info.as_deref().unwrap_or("") == other.to_store_info.as_deref().unwrap_or("")
+    }
+}
 
 // test create default TextualObjectTicket
 #[cfg(test)]
@@ -76,6 +118,18 @@ mod tests {
         assert_eq!(ticket.to_store_url, None);
         assert_eq!(ticket.to_store_info, None);
         assert!(ticket.to_marker.left_marker.len() > 0);
+        assert_eq!(ticket.to_updated.nanosecond(), 0);
+    }
+
+    #[test]
+    fn test_ticket_equality_ignores_private_meta_data() {
+        let ticket = ToTicket::default();
+        let mut other = ticket.clone();
+        other.id = "local_id".to_string();
+        other.to_store_url = Some(String::new());
+        assert_eq!(ticket, other);
+        other.values.insert("key1".to_string(), "value1".into());
+        assert_ne!(ticket, other);
     }
 
 
@@ -8,7 +8,7 @@ pub fn print_minimal_ticket(ticket_id: &str, opt: Option<ToMarker>) -> String {
     // create a list of string to be added
     let mut print_label: Vec<String> = Vec::new();
     // add id
-    print_label.push(format!("id: {}", ticket_id));
+    print_label.push(format!("id: {}", to_marker.escape_value(ticket_id)));
     // join all the strings in the list with the a separator |
     // and join with the to_marker.left_marker and to_marker.right_marker
     let mut result = String::new();
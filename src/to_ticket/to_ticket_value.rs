@@ -0,0 +1,214 @@
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+/// Value of a ticket entry.
+/// Printed values are read back as the type they look like, e.g. `12` is a number and `2020-01-31` a date;
+/// a string that looks like another type is printed in double quotes.
+/// A key repeated in a ticket, e.g. `tag: a | tag: b`, holds a list. A list is printed as its key repeated,
+/// so a list of one item reads back as the item itself, and an empty list is not printed.
+/// Serialized values are plain JSON, except dates which are tagged, e.g. `{"date": "2020-01-31"}`,
+/// so that a string that looks like a date is read back as a string; see `to_json` for plain JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ToTicketValue {
+    Bool(bool),
+    Number(Number),
+    Date(#[serde(with = "tagged_date")] NaiveDate),
+    String(String),
+    List(Vec<ToTicketValue>),
+}
+
+// a date in JSON as `{"date": "2020-01-31"}`
+mod tagged_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct TaggedDate {
+        date: NaiveDate,
+    }
+
+    pub fn serialize<S: Serializer>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error> {
+        TaggedDate { date: *date }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDate, D::Error> {
+        TaggedDate::deserialize(deserializer).map(|tagged| tagged.date)
+    }
+}
+
+impl ToTicketValue {
+    /// read a printed, unescaped value: a quoted string, or the type the text looks like
+    pub fn from_printed(text: &str) -> Self {
+        if text.len() >= 2 && text.starts_with('"') && text.ends_with('"') {
+            return ToTicketValue::String(text[1..text.len() - 1].to_string());
+        }
+        ToTicketValue::infer(text)
+    }
+
+    // only texts printed back exactly the same are read as another type than string, e.g. not `012` or `1.50`
+    fn infer(text: &str) -> Self {
+        match text {
+            "true" => return ToTicketValue::Bool(true),
+            "false" => return ToTicketValue::Bool(false),
+            _ => {}
+        }
+        if let Ok(integer) = text.parse::<i64>() {
+            if integer.to_string() == text {
+                return ToTicketValue::Number(integer.into());
+            }
+        }
+        if let Some(number) = text.parse::<f64>().ok().and_then(Number::from_f64) {
+            if number.to_string() == text {
+                return ToTicketValue::Number(number);
+            }
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+            if date.to_string() == text {
+                return ToTicketValue::Date(date);
+            }
+        }
+        ToTicketValue::String(text.to_string())
+    }
+
+    /// text of a single value as printed in a ticket, before escaping; lists are printed as repeated keys instead
+    pub fn printed(&self) -> String {
+        match self {
+            ToTicketValue::String(text) => {
+                // surrounding whitespace is not significant, the parser trims it
+                let text = text.trim();
+                if ToTicketValue::from_printed(text) == ToTicketValue::String(text.to_string()) {
+                    text.to_string()
+                } else {
+                    format!("\"{}\"", text)
+                }
+            }
+            other => other.to_string(),
+        }
+    }
+
+    /// the single values, i.e. the items of a list, nested lists included, or the value itself
+    pub fn items(&self) -> Vec<&ToTicketValue> {
+        match self {
+            ToTicketValue::List(items) => items.iter().flat_map(|item| item.items()).collect(),
+            single => vec![single],
+        }
+    }
+
+    /// the value as plain JSON, dates as strings, e.g. for the JSON of a TO or a template
+    pub fn to_json(&self) -> Value {
+        match self {
+            ToTicketValue::Bool(value) => Value::Bool(*value),
+            ToTicketValue::Number(value) => Value::Number(value.clone()),
+            ToTicketValue::Date(value) => Value::String(value.to_string()),
+            ToTicketValue::String(value) => Value::String(value.clone()),
+            ToTicketValue::List(items) => Value::Array(items.iter().map(|item| item.to_json()).collect()),
+        }
+    }
+
+    /// the text of a string value
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ToTicketValue::String(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// plain text of the value, list items separated by commas
+impl fmt::Display for ToTicketValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToTicketValue::Bool(value) => write!(f, "{}", value),
+            ToTicketValue::Number(value) => write!(f, "{}", value),
+            ToTicketValue::Date(value) => write!(f, "{}", value),
+            ToTicketValue::String(value) => write!(f, "{}", value),
+            ToTicketValue::List(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "{}", items.join(", "))
+            }
+        }
+    }
+}
+
+/// only a string value equals a text; e.g. the number 12 does not equal "12"
+impl PartialEq<str> for ToTicketValue {
+    fn eq(&self, other: &str) -> bool {
+        matches!(self, ToTicketValue::String(text) if text == other)
+    }
+}
+
+impl From<&str> for ToTicketValue {
+    fn from(text: &str) -> Self {
+        ToTicketValue::String(text.to_string())
+    }
+}
+
+impl From<String> for ToTicketValue {
+    fn from(text: String) -> Self {
+        ToTicketValue::String(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_printed() {
+        assert_eq!(ToTicketValue::from_printed("12"), ToTicketValue::Number(12.into()));
+        assert_eq!(ToTicketValue::from_printed("-1.5"), ToTicketValue::Number(Number::from_f64(-1.5).unwrap()));
+        assert_eq!(ToTicketValue::from_printed("true"), ToTicketValue::Bool(true));
+        assert_eq!(ToTicketValue::from_printed("2020-01-31"), ToTicketValue::Date(NaiveDate::from_ymd_opt(2020, 1, 31).unwrap()));
+        assert_eq!(ToTicketValue::from_printed("\"12\""), ToTicketValue::from("12"));
+        // not printed back the same, so kept as written
+        assert_eq!(ToTicketValue::from_printed("012"), ToTicketValue::from("012"));
+        assert_eq!(ToTicketValue::from_printed("1.50"), ToTicketValue::from("1.50"));
+        assert_eq!(ToTicketValue::from_printed("2020-1-31"), ToTicketValue::from("2020-1-31"));
+        assert_eq!(ToTicketValue::from_printed("\""), ToTicketValue::from("\""));
+    }
+
+    #[test]
+    fn test_printed_round_trip() {
+        for text in ["12", "true", "2020-01-31", "\"quoted\"", "plain", ""] {
+            let value = ToTicketValue::from(text);
+            assert_eq!(ToTicketValue::from_printed(&value.printed()), value);
+        }
+        assert_eq!(ToTicketValue::from("12").printed(), "\"12\"");
+        assert_eq!(ToTicketValue::Number(12.into()).printed(), "12");
+    }
+
+    #[test]
+    fn test_eq_str() {
+        assert_eq!(&ToTicketValue::from("12"), "12");
+        assert_ne!(&ToTicketValue::Number(12.into()), "12");
+        assert_ne!(&ToTicketValue::List(vec!["a".into(), "b".into()]), "a, b");
+    }
+
+    #[test]
+    fn test_json() {
+        let values = vec![
+            ToTicketValue::Number(12.into()),
+            ToTicketValue::Bool(false),
+            ToTicketValue::from("text"),
+            ToTicketValue::List(vec![ToTicketValue::from("a"), ToTicketValue::from("b")]),
+        ];
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, r#"[12,false,"text",["a","b"]]"#);
+        let read: Vec<ToTicketValue> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, values);
+
+        // a string that looks like a date stays a string, and a date stays a date
+        let read: ToTicketValue = serde_json::from_str(r#""2020-01-31""#).unwrap();
+        assert_eq!(read, ToTicketValue::from("2020-01-31"));
+        let date = ToTicketValue::Date(NaiveDate::from_ymd_opt(2020, 1, 31).unwrap());
+        let json = serde_json::to_string(&date).unwrap();
+        assert_eq!(json, r#"{"date":"2020-01-31"}"#);
+        assert_eq!(serde_json::from_str::<ToTicketValue>(&json).unwrap(), date);
+        assert_eq!(ToTicketValue::List(vec![date, ToTicketValue::Number(12.into())]).to_json(), serde_json::json!(["2020-01-31", 12]));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
+use crate::to_ticket::to_ticket_spec::{ToTicketField, ToTicketSpec};
 use crate::to_ticket::to_ticket_struct::ToTicket;
 
 // writer methods
@@ -8,34 +9,53 @@ impl ToTicket {
         let opt = opt.unwrap_or_default();
         // create a list of string to be added
         let mut print_label: Vec<String> = Vec::new();
+        let spec = &opt.ticket_spec;
         // add id
-        print_label.push(format!("id: {}", self.ticket_id));
+        print_label.push(format!("{}: {}", spec.key(ToTicketField::Id), self.to_marker.escape_value(self.ticket_id.trim())));
 
         if !opt.minimal {
             // add values; use reverse order otherwise the first inserted is the last printed
             for (key, value) in self.values.iter() {
+                // surrounding whitespace is not significant, the parser trims it
+                let key = key.trim();
                 // ignore if the key is reserved, i.e. the same as the public or private meta-data fields
                 if key == "to_updated" || key == "to_store_id" || key == "to_store_info" || key == "to_marker" {
                     continue;
                 }
-                print_label.push(format!("{}: {}", key, value));
+                // keys that the parser would read back as meta-data are escaped
+                let written_key = match spec.field_of(key) {
+                    Some(_) => self.to_marker.escape_reserved_key(key),
+                    None => self.to_marker.escape_key(key),
+                };
+                // a list is printed as the key repeated for each item
+                for item in value.items() {
+                    print_label.push(format!("{}: {}", written_key, self.to_marker.escape_value(&item.printed())));
+                }
             }
 
             /*
-        Add meta-data if needed and at the end of the ticket
+        Add meta-data if needed and at the end of the ticket, in the order of the spec
          */
-            // check opt to see if include_updated
-            // if so, print date string without nano-second
-            if opt.include_updated {
-                print_label.push(format!("updated: {}", self.to_updated.format("%Y-%m-%d %H:%M:%S")));
-            }
-            // check opt to see if include_store_info, length of store_id is not None, and length of store_id is not 0
-            if opt.include_store_info && self.to_store_url.is_some() && self.to_store_url.as_ref().unwrap().len() > 0 {
-                print_label.push(format!("store_info: {}", self.to_store_url.clone().unwrap()));
+            for field in spec.fields() {
+                let value = match field {
+                    ToTicketField::Id => continue,
+                    // print date string without nano-second; the parser reads dates without offset as UTC
+                    ToTicketField::Updated if opt.include_updated => Some(self.to_updated.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ToTicketField::Created if opt.include_created => self.to_created.map(|created| created.naive_utc().format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ToTicketField::StoreInfo if opt.include_store_info => self.to_store_info.clone(),
+                    // the parser reads store_id into store_url
+                    ToTicketField::StoreUrl if opt.include_store_id => self.to_store_url.clone(),
+                    ToTicketField::Label if opt.include_label => self.to_label.clone(),
+                    _ => None,
+                };
+                // empty store url and store info are not printed
+                if let Some(value) = value.filter(|value| !value.trim().is_empty()) {
+                    print_label.push(format!("{}: {}", self.to_marker.escape_key(&spec.key(field)), self.to_marker.escape_value(value.trim())));
+                }
             }
-            // check opt to see if include_store_id, length of store_id is not None, and length of store_id is not 0
-            if opt.include_store_id && self.to_store_info.is_some() && self.to_store_info.as_ref().unwrap().len() > 0 {
-                print_label.push(format!("store_id: {}", self.to_store_info.clone().unwrap()));
+        } else if opt.include_label && spec.fields().contains(&ToTicketField::Label) {
+            if let Some(label) = self.to_label.as_deref().filter(|label| !label.trim().is_empty()) {
+                print_label.push(format!("{}: {}", self.to_marker.escape_key(&spec.key(ToTicketField::Label)), self.to_marker.escape_value(label.trim())));
             }
         }
         // join all the strings in the list with the a separator |
@@ -56,7 +76,19 @@ impl ToTicket {
             include_store_info: false,
             include_store_id: false,
             include_created: false,
+            include_label: false,
             minimal: true,
+            ..Default::default()
+        }))
+    }
+
+    /// print the id with the display label, e.g. `[[id: abc12 | label: Foucault 1975, Discipline and Punish]]`, or only the id without label
+    pub fn print_labelled(&self) -> String {
+        self.print(Some(ToTicketPrintOption {
+            include_label: true,
+            minimal: true,
+            ticket_spec: ToTicketSpec::with_label(),
+            ..Default::default()
         }))
     }
 
@@ -69,7 +101,7 @@ impl ToTicket {
 // test module
 #[cfg(test)]
 mod tests {
-    use chrono::{FixedOffset, TimeZone, Utc};
+    use chrono::{FixedOffset, Offset, TimeZone, Utc};
 
     use crate::to::to_struct::TextualObject;
     use crate::to_ticket::to_ticket_option::ToTicketPrintOption;
@@ -88,8 +120,8 @@ mod tests {
     fn test_print_ticket_with_values() {
         let mut ticket = ToTicket::default();
         ticket.ticket_id = "test_id".to_string();
-        ticket.values.insert("key1".to_string(), "value1".to_string());
-        ticket.values.insert("key2".to_string(), "value2".to_string());
+        ticket.values.insert("key1".to_string(), "value1".into());
+        ticket.values.insert("key2".to_string(), "value2".into());
         let print_label = ticket.print(None);
         assert_eq!(print_label, format!("[[id: test_id | key1: value1 | key2: value2 | updated: {}]]", Utc::now().format("%Y-%m-%d %H:%M:%S")));
     }
@@ -99,27 +131,44 @@ mod tests {
     fn test_print_ticket_with_values_and_meta_data() {
         let mut ticket = ToTicket::default();
         ticket.ticket_id = "test_id".to_string();
-        ticket.values.insert("key1".to_string(), "value1".to_string());
-        ticket.values.insert("key2".to_string(), "value2".to_string());
+        ticket.values.insert("key1".to_string(), "value1".into());
+        ticket.values.insert("key2".to_string(), "value2".into());
         ticket.to_updated = FixedOffset::east(0).ymd(2019, 1, 1).and_hms(0, 0, 0);
-        ticket.to_store_url = Some("store_info_value".to_string());
-        ticket.to_store_info = Some("store_id_value".to_string());
+        ticket.to_store_url = Some("store_id_value".to_string());
+        ticket.to_store_info = Some("store_info_value".to_string());
         let print_label = ticket.print(None);
         assert_eq!(print_label, format!("[[id: test_id | key1: value1 | key2: value2 | updated: 2019-01-01 00:00:00 | store_info: store_info_value | store_id: store_id_value]]"));
     }
 
+    // created is printed after updated, unless left out by the print option
+    #[test]
+    fn test_print_ticket_with_created() {
+        let ticket = ToTicket {
+            ticket_id: "test_id".to_string(),
+            to_updated: Utc.fix().with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap(),
+            to_created: Some(Utc.fix().with_ymd_and_hms(2018, 1, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(ticket.print(None), "[[id: test_id | updated: 2019-01-01 00:00:00 | created: 2018-01-01 00:00:00]]");
+        let print_label = ticket.print(Some(ToTicketPrintOption {
+            include_created: false,
+            ..Default::default()
+        }));
+        assert_eq!(print_label, "[[id: test_id | updated: 2019-01-01 00:00:00]]");
+    }
+
     // test when print ticket values has keys that conflict with meta-data
     #[test]
     fn test_print_ticket_with_values_and_meta_data_conflict() {
         let mut ticket = ToTicket::default();
         ticket.ticket_id = "test_id".to_string();
-        ticket.values.insert("key1".to_string(), "value1".to_string());
-        ticket.values.insert("key2".to_string(), "value2".to_string());
+        ticket.values.insert("key1".to_string(), "value1".into());
+        ticket.values.insert("key2".to_string(), "value2".into());
         ticket.to_updated = FixedOffset::east(0).ymd(2019, 1, 1).and_hms(0, 0, 0);
         ticket.to_store_url = Some("correct_store_info_value".to_string());
         ticket.to_store_info = Some("correct_store_info_value".to_string());
-        ticket.values.insert("to_store_id".to_string(), "wrong_store_id_value".to_string());
-        ticket.values.insert("to_store_info".to_string(), "wrong_store_info_value".to_string());
+        ticket.values.insert("to_store_id".to_string(), "wrong_store_id_value".into());
+        ticket.values.insert("to_store_info".to_string(), "wrong_store_info_value".into());
         let print_label = ticket.print(None);
         assert_eq!(print_label, format!("[[id: test_id | key1: value1 | key2: value2 | updated: 2019-01-01 00:00:00 | store_info: correct_store_info_value | store_id: correct_store_info_value]]"));
     }
@@ -136,6 +185,7 @@ mod tests {
             include_store_id: true,
             include_created: true,
             minimal: true, // i set other options to true but those should be overridden when minimal is true
+            ..Default::default()
         }));
         assert_eq!(minimal_label, format!("[[id: {}]]", ticket_id));
         let _not_minimal_label = ticket.print(Some(ToTicketPrintOption {
@@ -144,6 +194,7 @@ mod tests {
             include_store_id: true,
             include_created: true,
             minimal: false,
+            ..Default::default()
         }));
     }
 }
\ No newline at end of file
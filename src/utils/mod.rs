@@ -3,3 +3,4 @@ pub mod id_generator;
 pub(crate) mod get_random_test_database_dir;
 pub(crate) mod split_store_path;
 pub(crate) mod check_if_file_exists;
+pub(crate) mod unified_diff;
@@ -0,0 +1,160 @@
+// unified diff of two texts, line by line, as `diff -u` prints it; empty when the texts are the same
+
+#[derive(Clone, Copy, PartialEq)]
+enum Edit {
+    Equal,
+    Delete,
+    Insert,
+}
+
+// an edit with the old and new line it starts at
+#[derive(Clone, Copy)]
+struct Step {
+    edit: Edit,
+    old_line: usize,
+    new_line: usize,
+}
+
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let steps = shortest_edit(&old_lines, &new_lines);
+    if steps.iter().all(|step| step.edit == Edit::Equal) {
+        return String::new();
+    }
+
+    let mut diff = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let changes: Vec<usize> = steps.iter().enumerate().filter(|(_, step)| step.edit != Edit::Equal).map(|(index, _)| index).collect();
+    let mut hunk_start = 0;
+    while hunk_start < changes.len() {
+        // changes closer than twice the context share a hunk
+        let mut hunk_end = hunk_start;
+        while hunk_end + 1 < changes.len() && changes[hunk_end + 1] - changes[hunk_end] <= 2 * context {
+            hunk_end += 1;
+        }
+        let first = changes[hunk_start].saturating_sub(context);
+        let last = (changes[hunk_end] + context).min(steps.len() - 1);
+        let hunk = &steps[first..=last];
+
+        let old_length = hunk.iter().filter(|step| step.edit != Edit::Insert).count();
+        let new_length = hunk.iter().filter(|step| step.edit != Edit::Delete).count();
+        // an empty range starts at the line before it
+        let old_start = if old_length == 0 { hunk[0].old_line } else { hunk[0].old_line + 1 };
+        let new_start = if new_length == 0 { hunk[0].new_line } else { hunk[0].new_line + 1 };
+        diff.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_length, new_start, new_length));
+        for step in hunk {
+            let (prefix, line) = match step.edit {
+                Edit::Equal => (' ', old_lines[step.old_line]),
+                Edit::Delete => ('-', old_lines[step.old_line]),
+                Edit::Insert => ('+', new_lines[step.new_line]),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+        hunk_start = hunk_end + 1;
+    }
+    diff
+}
+
+// shortest edit script with Myers' algorithm, in order
+fn shortest_edit(old: &[&str], new: &[&str]) -> Vec<Step> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let index = |k: isize| (offset + k) as usize;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // the diagonals -(d + 1)..=d + 1 of v at the start of each round, the only ones round d reads, to walk back the edits
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    'rounds: for d in 0..=max {
+        trace.push(v[index(-d - 1)..=index(d + 1)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) { v[index(k + 1)] } else { v[index(k - 1)] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'rounds;
+            }
+        }
+    }
+
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, round) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| round[(k + d + 1) as usize];
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) { k + 1 } else { k - 1 };
+        let previous_x = at(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            steps.push(Step { edit: Edit::Equal, old_line: x as usize, new_line: y as usize });
+        }
+        if d > 0 {
+            if x == previous_x {
+                steps.push(Step { edit: Edit::Insert, old_line: x as usize, new_line: previous_y as usize });
+            } else {
+                steps.push(Step { edit: Edit::Delete, old_line: previous_x as usize, new_line: y as usize });
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    steps.reverse();
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_text() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a", "b", 3), "");
+        assert_eq!(unified_diff("", "", "a", "b", 3), "");
+    }
+
+    #[test]
+    fn test_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\neleven\n";
+        let diff = unified_diff(old, new, "a/text", "b/text", 1);
+        assert_eq!(diff, "--- a/text\n+++ b/text\n@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -10,1 +10,2 @@\n 10\n+eleven\n");
+        // close changes share a hunk
+        let diff = unified_diff(old, new, "a/text", "b/text", 5);
+        assert_eq!(diff.matches("@@ -").count(), 1);
+    }
+
+    #[test]
+    fn test_edit_script() {
+        let old: Vec<&str> = "a b c a b b a".split(' ').collect();
+        let new: Vec<&str> = "c b a b a c".split(' ').collect();
+        let steps = shortest_edit(&old, &new);
+        // the shortest script of the example of Myers' paper has 5 edits
+        assert_eq!(steps.iter().filter(|step| step.edit != Edit::Equal).count(), 5);
+        let kept = |skipped: Edit| -> Vec<usize> {
+            steps.iter().filter(|step| step.edit != skipped).map(|step| if skipped == Edit::Insert { step.old_line } else { step.new_line }).collect()
+        };
+        assert_eq!(kept(Edit::Insert), (0..old.len()).collect::<Vec<usize>>());
+        assert_eq!(kept(Edit::Delete), (0..new.len()).collect::<Vec<usize>>());
+        for step in steps.iter().filter(|step| step.edit == Edit::Equal) {
+            assert_eq!(old[step.old_line], new[step.new_line]);
+        }
+    }
+
+    #[test]
+    fn test_no_newline_at_end() {
+        let diff = unified_diff("a", "b", "old", "new", 3);
+        assert_eq!(diff, "--- old\n+++ new\n@@ -1,1 +1,1 @@\n-a\n\\ No newline at end of file\n+b\n\\ No newline at end of file\n");
+        let diff = unified_diff("", "a\n", "old", "new", 3);
+        assert_eq!(diff, "--- old\n+++ new\n@@ -0,0 +1,1 @@\n+a\n");
+    }
+}